@@ -0,0 +1,427 @@
+//! Replays recorded interactions against an in-memory guild and checks what the bot did.
+//!
+//! Usage: `cargo run --features fake --bin replay -- [FIXTURE_OR_DIRECTORY...]`, defaulting
+//! to `fixtures/`.
+//! Each fixture runs in its own process so scenarios never share a bounty store.
+//!
+//! A fixture is a JSON scenario:
+//!
+//! - `env`: environment variables the scenario runs with, e.g. `BOUNTY_CATEGORY`.
+//! - `guild`: the guild `id`, the `categories` and `roles` it starts with (by name) and the
+//!   `users` the bot can look up.
+//! - `steps`: interaction payloads as Discord sends them, each with optional expectations:
+//!   - `calls`: every Discord call the interaction caused, in order. Either a method name or
+//!     `{"method", "target", "body"}`, where `body` only needs to contain the given fields.
+//!   - `bounties`: `{"number", "status", "escrowed", "category"}` checked in the store, with
+//!     `escrowed` as `"50 USD"` or `"none"` and `category` naming the channel's category.
+//!
+//! IDs only known while replaying are written as `${name}` in interactions and expected calls:
+//! `${role:NAME}` and `${category:NAME}` for the guild setup, `${bounty:NUMBER}` and
+//! `${channel:NUMBER}` for stored bounties, and `${KIND}` (e.g. `${confirm}`) for the custom
+//! ID of the last button of that kind the bot sent. Expected calls see the IDs bound by
+//! their own step.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+use serde::Deserialize;
+use serenity::{
+    json::Value,
+    model::{
+        id::{ChannelId, GuildId},
+        prelude::interaction::Interaction,
+        user::User,
+    },
+};
+
+use discord_bot_rust::commands::{component_id::ComponentId, registry};
+use discord_bot_rust::discord_util::fake::{Call, FakeGuild};
+use discord_bot_rust::storage::store;
+
+const DEFAULT_FIXTURES: &str = "fixtures";
+
+/// Settings a scenario must not inherit from the shell running it.
+const CONFIG_VARS: [&str; 14] = [
+    "BOUNTY_CATEGORY",
+    "ARCHIVE_CATEGORY",
+    "STAFF_ROLES",
+    "COMPLETE_ROLES",
+    "CANCEL_ROLES",
+    "FORCE_CLOSE_ROLES",
+    "ACHIEVEMENTS_FILE",
+    "SEASONS_FILE",
+    "SEASON_CHANNEL",
+    "STAFF_LOG_CHANNEL",
+    "PAYOUT_PROVIDER",
+    "PAYOUT_CSV_FILE",
+    "PAYOUT_WEBHOOK_URL",
+    "DATA_FILE",
+];
+
+#[derive(Deserialize)]
+struct Scenario {
+    #[serde(default)]
+    env: HashMap<String, String>,
+    guild: GuildSetup,
+    steps: Vec<Step>,
+}
+
+#[derive(Deserialize)]
+struct GuildSetup {
+    id: GuildId,
+    #[serde(default)]
+    categories: Vec<String>,
+    #[serde(default)]
+    roles: Vec<String>,
+    #[serde(default)]
+    users: Vec<User>,
+}
+
+#[derive(Deserialize)]
+struct Step {
+    interaction: Value,
+    /// Parsed once the step ran, as it may mention IDs bound by the step itself.
+    calls: Option<Value>,
+    #[serde(default)]
+    bounties: Vec<ExpectedBounty>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExpectedCall {
+    Method(String),
+    Call {
+        method: String,
+        target: Option<String>,
+        body: Option<Value>,
+    },
+}
+
+#[derive(Deserialize)]
+struct ExpectedBounty {
+    number: u32,
+    status: Option<String>,
+    escrowed: Option<String>,
+    category: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let mut paths: Vec<PathBuf> = env::args().skip(1).map(PathBuf::from).collect();
+    if paths.is_empty() {
+        paths.push(PathBuf::from(DEFAULT_FIXTURES));
+    }
+
+    let fixtures = match fixtures(&paths) {
+        Ok(fixtures) => fixtures,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(2);
+        }
+    };
+
+    if let [fixture] = fixtures.as_slice() {
+        match replay(fixture).await {
+            Ok(()) => println!("ok {}", fixture.display()),
+            Err(err) => {
+                println!("FAILED {}: {}", fixture.display(), err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut failed = 0;
+    for fixture in &fixtures {
+        let status = env::current_exe()
+            .and_then(|exe| Command::new(exe).arg(fixture).status())
+            .unwrap_or_else(|err| panic!("Could not replay {}: {}", fixture.display(), err));
+        if !status.success() {
+            failed += 1;
+        }
+    }
+    println!("{} scenarios, {} failed", fixtures.len(), failed);
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+/// Expands directories into the JSON fixtures they contain.
+fn fixtures(paths: &[PathBuf]) -> Result<Vec<PathBuf>, String> {
+    let mut fixtures = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            fixtures.push(path.clone());
+            continue;
+        }
+        let entries = fs::read_dir(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let mut found: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        found.sort();
+        fixtures.extend(found);
+    }
+    if fixtures.is_empty() {
+        return Err(String::from("No fixtures to replay"));
+    }
+    Ok(fixtures)
+}
+
+async fn replay(path: &Path) -> Result<(), String> {
+    let scenario: Scenario = fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|err| err.to_string()))?;
+
+    let data_file = env::temp_dir().join(format!("replay-{}.json", process::id()));
+    for var in CONFIG_VARS {
+        env::remove_var(var);
+    }
+    for (name, value) in &scenario.env {
+        env::set_var(name, value);
+    }
+    env::set_var("DATA_FILE", &data_file);
+
+    let result = run(scenario).await;
+    let _ = fs::remove_file(data_file);
+    result
+}
+
+async fn run(scenario: Scenario) -> Result<(), String> {
+    let guild = FakeGuild::new(scenario.guild.id);
+    let mut vars = HashMap::new();
+    vars.insert(String::from("guild"), scenario.guild.id.to_string());
+    for name in &scenario.guild.categories {
+        let id = guild.add_category(name);
+        vars.insert(format!("category:{}", name), id.to_string());
+    }
+    for name in &scenario.guild.roles {
+        let id = guild.add_role(name);
+        vars.insert(format!("role:{}", name), id.to_string());
+    }
+    for user in scenario.guild.users {
+        guild.add_user(user);
+    }
+
+    for (number, step) in scenario.steps.iter().enumerate() {
+        let failures = run_step(&guild, step, &mut vars).await?;
+        if !failures.is_empty() {
+            return Err(format!("step {}:\n  {}", number + 1, failures.join("\n  ")));
+        }
+    }
+    Ok(())
+}
+
+/// Dispatches one interaction and lists every expectation it did not meet.
+async fn run_step(
+    guild: &FakeGuild,
+    step: &Step,
+    vars: &mut HashMap<String, String>,
+) -> Result<Vec<String>, String> {
+    let payload = substitute(&step.interaction.to_string(), vars)?;
+    let interaction: Interaction = serde_json::from_str(&payload)
+        .map_err(|err| format!("invalid interaction payload: {}", err))?;
+
+    registry::dispatch(guild, interaction).await;
+    let calls = guild.take_calls();
+    bind(&calls, vars);
+
+    let mut failures = Vec::new();
+    if let Some(expected) = &step.calls {
+        let expected: Vec<ExpectedCall> =
+            serde_json::from_str(&substitute(&expected.to_string(), vars)?)
+                .map_err(|err| format!("invalid expected calls: {}", err))?;
+        check_calls(&calls, &expected, &mut failures);
+    }
+    for bounty in &step.bounties {
+        check_bounty(guild, bounty, &mut failures);
+    }
+    Ok(failures)
+}
+
+/// Replaces every `${name}` in `payload` with its value.
+fn substitute(payload: &str, vars: &HashMap<String, String>) -> Result<String, String> {
+    let mut result = String::new();
+    let mut rest = payload;
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| String::from("unterminated ${ in interaction"))?;
+        let name = &rest[start + 2..start + end];
+        let value = vars
+            .get(name)
+            .ok_or_else(|| format!("${{{}}} is not known at this step", name))?;
+        result.push_str(&rest[..start]);
+        result.push_str(value);
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Remembers the buttons the bot sent and the bounties it stored for later steps.
+fn bind(calls: &[Call], vars: &mut HashMap<String, String>) {
+    for call in calls {
+        let mut custom_ids = Vec::new();
+        collect_custom_ids(&call.body, &mut custom_ids);
+        for custom_id in custom_ids {
+            if let Ok(id) = ComponentId::decode(&custom_id) {
+                vars.insert(id.kind().to_string(), custom_id);
+            }
+        }
+    }
+
+    store::read(|store| {
+        for (id, bounty) in &store.bounties {
+            vars.insert(format!("bounty:{}", bounty.bounty_number), id.to_string());
+            if let Some(channel) = bounty.channel_id {
+                vars.insert(
+                    format!("channel:{}", bounty.bounty_number),
+                    channel.to_string(),
+                );
+            }
+        }
+    });
+}
+
+fn collect_custom_ids(body: &Value, found: &mut Vec<String>) {
+    match body {
+        Value::Object(map) => {
+            for (key, value) in map {
+                match value {
+                    Value::String(custom_id) if key == "custom_id" => found.push(custom_id.clone()),
+                    _ => collect_custom_ids(value, found),
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                collect_custom_ids(value, found);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn check_calls(calls: &[Call], expected: &[ExpectedCall], failures: &mut Vec<String>) {
+    let methods: Vec<&str> = calls.iter().map(|call| call.method).collect();
+    if calls.len() != expected.len() {
+        failures.push(format!(
+            "expected {} calls, got {}: {:?}",
+            expected.len(),
+            calls.len(),
+            methods
+        ));
+        return;
+    }
+
+    for (index, (call, expected)) in calls.iter().zip(expected).enumerate() {
+        let (method, target, body) = match expected {
+            ExpectedCall::Method(method) => (method, None, None),
+            ExpectedCall::Call {
+                method,
+                target,
+                body,
+            } => (method, target.as_ref(), body.as_ref()),
+        };
+        if call.method != method {
+            failures.push(format!(
+                "call {}: expected {}, got {:?}",
+                index + 1,
+                method,
+                methods
+            ));
+            continue;
+        }
+        if target.is_some_and(|target| *target != call.target) {
+            failures.push(format!(
+                "call {} ({}): expected target {}, got {}",
+                index + 1,
+                method,
+                target.unwrap(),
+                call.target
+            ));
+        }
+        if body.is_some_and(|body| !contains(&call.body, body)) {
+            failures.push(format!(
+                "call {} ({}): body {} does not contain {}",
+                index + 1,
+                method,
+                call.body,
+                body.unwrap()
+            ));
+        }
+    }
+}
+
+/// Whether `actual` has every field of `expected`, comparing arrays element by element.
+fn contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected.iter().all(|(key, value)| {
+            actual
+                .get(key)
+                .is_some_and(|actual| contains(actual, value))
+        }),
+        (Value::Array(actual), Value::Array(expected)) => {
+            actual.len() == expected.len()
+                && actual
+                    .iter()
+                    .zip(expected)
+                    .all(|(actual, expected)| contains(actual, expected))
+        }
+        _ => actual == expected,
+    }
+}
+
+fn check_bounty(guild: &FakeGuild, expected: &ExpectedBounty, failures: &mut Vec<String>) {
+    let (id, bounty) = match store::find_by_number(expected.number) {
+        Some(found) => found,
+        None => {
+            failures.push(format!("bounty {} was not stored", expected.number));
+            return;
+        }
+    };
+
+    if let Some(status) = &expected.status {
+        if bounty.status.as_str() != status {
+            failures.push(format!(
+                "bounty {}: expected status {}, got {}",
+                expected.number,
+                status,
+                bounty.status.as_str()
+            ));
+        }
+    }
+
+    if let Some(escrowed) = &expected.escrowed {
+        let held = store::read(|store| store.ledger.escrowed(&id))
+            .map(|(amount, currency)| format!("{} {}", amount, currency))
+            .unwrap_or_else(|| String::from("none"));
+        if held != *escrowed {
+            failures.push(format!(
+                "bounty {}: expected {} in escrow, got {}",
+                expected.number, escrowed, held
+            ));
+        }
+    }
+
+    if let Some(category) = &expected.category {
+        let actual = bounty
+            .channel_id
+            .and_then(|channel| guild.channel(channel))
+            .and_then(|channel| channel.parent_id)
+            .and_then(|parent: ChannelId| guild.channel(parent))
+            .map(|parent| parent.name)
+            .unwrap_or_else(|| String::from("none"));
+        if actual != *category {
+            failures.push(format!(
+                "bounty {}: expected channel in {}, got {}",
+                expected.number, category, actual
+            ));
+        }
+    }
+}
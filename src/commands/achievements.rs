@@ -0,0 +1,53 @@
+use serenity::{builder::CreateMessage, model::prelude::GuildId};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::commands::bounty::Bounty;
+use crate::discord_util::api::DiscordApi;
+use crate::error::Result;
+use crate::storage::{achievements, store};
+
+/// Unlocks the milestones the hunter reached with this bounty, announcing them in the bounty
+/// channel and granting the configured roles.
+pub async fn award(
+    api: &dyn DiscordApi,
+    guild_id: GuildId,
+    bounty_id: Uuid,
+    bounty: &Bounty,
+) -> Result<()> {
+    let unlocked =
+        store::write(|store| achievements::unlock_new(store, bounty.hunter.id, bounty_id))??;
+
+    for milestone in unlocked {
+        let message = format!(
+            "🏆 {} unlocked **{}**: {}",
+            bounty.hunter.name, milestone.name, milestone.description
+        );
+        match bounty.channel_id {
+            Some(channel_id) => {
+                let mut announcement = CreateMessage::default();
+                announcement.content(message);
+                if let Err(err) = api.send_message(channel_id, announcement).await {
+                    warn!(error = %err, "Could not announce {}", milestone.id);
+                }
+            }
+            None => warn!("Bounty has no channel to announce {} in", milestone.id),
+        }
+
+        if let Some(role_id) = milestone.role_id {
+            if let Err(err) = api
+                .add_member_role(
+                    guild_id,
+                    bounty.hunter.id,
+                    role_id,
+                    "Bounty achievement unlocked",
+                )
+                .await
+            {
+                warn!(error = %err, "Could not grant role for {}", milestone.id);
+            }
+        }
+    }
+
+    Ok(())
+}
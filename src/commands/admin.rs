@@ -0,0 +1,402 @@
+use serenity::{
+    builder::{
+        CreateApplicationCommandOption, CreateInteractionResponse, CreateMessage,
+        EditInteractionResponse,
+    },
+    model::{
+        prelude::{
+            command::CommandOptionType,
+            interaction::{
+                application_command::{
+                    ApplicationCommandInteraction, CommandDataOption, CommandDataOptionValue,
+                },
+                InteractionResponseType, MessageFlags,
+            },
+        },
+        user::User,
+        Timestamp,
+    },
+};
+
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::commands::{
+    audit, autocomplete,
+    bounty::{in_guild, require_any_status, Bounty, BountyStatus},
+    registry::{respond, respond_slowly, Registry},
+    staff::{self, StaffAction},
+};
+use crate::discord_util::{self, api::DiscordApi};
+use crate::error::{required_var, Error, Result};
+use crate::logging;
+use crate::storage::{
+    audit::{AuditAction, AuditEvent},
+    events::{BountyEvent, EventKind},
+    store,
+};
+
+const MAX_LISTED: usize = 25;
+/// Bounties still open. A confirming bounty is left to its confirmation, which is creating
+/// its channel.
+const FORCE_CLOSABLE: &[BountyStatus] = &[
+    BountyStatus::Pending,
+    BountyStatus::Confirmed,
+    BountyStatus::Accepted,
+];
+
+pub fn register_routes(registry: &mut Registry) {
+    registry.subcommand("bounty", "list", register_list, |api, command| {
+        Box::pin(async move { respond(api, command, list(command)?).await })
+    });
+    registry.subcommand("bounty", "info", register_info, |api, command| {
+        Box::pin(async move { respond(api, command, info(command)?).await })
+    });
+    registry.subcommand(
+        "bounty",
+        "force-close",
+        register_force_close,
+        |api, command| Box::pin(force_close(api, command)),
+    );
+}
+
+pub fn register_list(
+    option: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    option
+        .name("list")
+        .description("List bounties (staff only)")
+        .kind(CommandOptionType::SubCommand)
+        .create_sub_option(|option| {
+            option
+                .name("status")
+                .description("Only show bounties with this status")
+                .kind(CommandOptionType::String)
+                .add_string_choice("Pending", "pending")
+                .add_string_choice("Confirmed", "confirmed")
+                .add_string_choice("Accepted", "accepted")
+                .add_string_choice("Declined", "declined")
+                .add_string_choice("Completed", "completed")
+                .add_string_choice("Cancelled", "cancelled")
+        })
+        .create_sub_option(|option| {
+            option
+                .name("user")
+                .description("Only show bounties this user listed or hunts")
+                .kind(CommandOptionType::User)
+        })
+}
+
+pub fn register_info(
+    option: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    option
+        .name("info")
+        .description("Show the details of a bounty (staff only)")
+        .kind(CommandOptionType::SubCommand)
+        .create_sub_option(|option| {
+            option
+                .name("bounty")
+                .description("The bounty number, or search by participant")
+                .kind(CommandOptionType::String)
+                .set_autocomplete(true)
+                .required(true)
+        })
+        .create_sub_option(|option| {
+            option
+                .name("at")
+                .description("Show the bounty as it was at this time, e.g. 2024-01-31T12:00:00Z")
+                .kind(CommandOptionType::String)
+        })
+}
+
+pub fn register_force_close(
+    option: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    option
+        .name("force-close")
+        .description("Cancel a bounty, refund its reward and archive its channel (staff only)")
+        .kind(CommandOptionType::SubCommand)
+        .create_sub_option(|option| {
+            option
+                .name("bounty")
+                .description("The bounty number, or search by participant")
+                .kind(CommandOptionType::String)
+                .set_autocomplete(true)
+                .required(true)
+        })
+        .create_sub_option(|option| {
+            option
+                .name("reason")
+                .description("Why the bounty is closed")
+                .kind(CommandOptionType::String)
+        })
+}
+
+pub fn list<'a>(command: &ApplicationCommandInteraction) -> Result<CreateInteractionResponse<'a>> {
+    if !staff::is_staff(&command.member)? {
+        return Err(Error::Permission(String::from(
+            "Only staff can list bounties",
+        )));
+    }
+
+    let mut status = None;
+    let mut user: Option<User> = None;
+    for arg in &command.data.options[0].options {
+        match &arg.resolved {
+            Some(CommandDataOptionValue::String(value)) if arg.name == "status" => {
+                status = BountyStatus::parse(value)
+            }
+            Some(CommandDataOptionValue::User(value, _)) if arg.name == "user" => {
+                user = Some(value.clone())
+            }
+            _ => warn!(option = %arg.name, "Unknown argument"),
+        }
+    }
+
+    let mut bounties: Vec<Bounty> = store::read(|store| {
+        store
+            .bounties
+            .values()
+            .filter(|b| status.is_none_or(|status| b.status == status))
+            .filter(|b| {
+                user.as_ref()
+                    .is_none_or(|u| b.lister.id == u.id || b.hunter.id == u.id)
+            })
+            .cloned()
+            .collect()
+    });
+    bounties.sort_by_key(|b| std::cmp::Reverse(b.created_at.map(|t| t.unix_timestamp())));
+
+    let mut description = String::new();
+    for bounty in bounties.iter().take(MAX_LISTED) {
+        description.push_str(&format!(
+            "**#{}** {} → {} — {}{}\n",
+            bounty.bounty_number,
+            bounty.lister.name,
+            bounty.hunter.name,
+            bounty.status.as_str(),
+            bounty
+                .channel_id
+                .map(|channel| format!(" — <#{}>", channel))
+                .unwrap_or_default()
+        ));
+    }
+    if bounties.len() > MAX_LISTED {
+        description.push_str(&format!("…and {} more", bounties.len() - MAX_LISTED));
+    }
+    if description.is_empty() {
+        description.push_str("No bounties match.");
+    }
+
+    Ok(CreateInteractionResponse::default()
+        .kind(InteractionResponseType::ChannelMessageWithSource)
+        .interaction_response_data(|d| {
+            d.flags(MessageFlags::EPHEMERAL)
+                .embed(|e| e.title("Bounties").description(description))
+        })
+        .clone())
+}
+
+pub fn info<'a>(command: &ApplicationCommandInteraction) -> Result<CreateInteractionResponse<'a>> {
+    if !staff::is_staff(&command.member)? {
+        return Err(Error::Permission(String::from(
+            "Only staff can look up bounties",
+        )));
+    }
+
+    let options = &command.data.options[0].options;
+    let (id, bounty) = bounty_reference(options)?;
+    let at = options
+        .iter()
+        .find_map(|arg| match &arg.resolved {
+            Some(CommandDataOptionValue::String(at)) if arg.name == "at" => Some(at),
+            _ => None,
+        })
+        .map(|at| {
+            Timestamp::parse(at.trim())
+                .map_err(|_| Error::Validation(format!("{} is not a valid time", at)))
+        })
+        .transpose()?;
+    let (bounty, escrowed) = match at {
+        Some(at) => (
+            store::bounty_at(&id, at).ok_or_else(|| {
+                Error::Validation(String::from("The bounty did not exist at that time"))
+            })?,
+            store::read(|store| store.ledger.escrowed_at(&id, at)),
+        ),
+        None => (bounty, store::read(|store| store.ledger.escrowed(&id))),
+    };
+
+    let mut fields = vec![
+        ("Status", bounty.status.as_str().to_string()),
+        ("Lister", bounty.lister.name.clone()),
+        ("Hunter", bounty.hunter.name.clone()),
+        ("Reward", format!("{} {}", bounty.reward, bounty.currency)),
+        (
+            "In escrow",
+            escrowed
+                .map(|(amount, currency)| format!("{} {}", amount, currency))
+                .unwrap_or_else(|| String::from("-")),
+        ),
+        (
+            "Channel",
+            bounty
+                .channel_id
+                .map(|channel| format!("<#{}>", channel))
+                .unwrap_or_else(|| String::from("-")),
+        ),
+    ];
+    for (name, timestamp) in [
+        ("Created", bounty.created_at),
+        ("Accepted", bounty.accepted_at),
+        ("Completed", bounty.completed_at),
+    ] {
+        if let Some(timestamp) = timestamp {
+            fields.push((name, format!("<t:{}:f>", timestamp.unix_timestamp())));
+        }
+    }
+    if let Some(payout) = &bounty.payout {
+        fields.push((
+            "Payout",
+            format!(
+                "{:?} via {}{}",
+                payout.status,
+                payout.provider,
+                payout
+                    .reference
+                    .as_ref()
+                    .map(|reference| format!(" ({})", reference))
+                    .unwrap_or_default()
+            ),
+        ));
+    }
+
+    Ok(CreateInteractionResponse::default()
+        .kind(InteractionResponseType::ChannelMessageWithSource)
+        .interaction_response_data(|d| {
+            d.flags(MessageFlags::EPHEMERAL).embed(|e| {
+                e.title(format!("Bounty #{}", bounty.bounty_number));
+                if let Some(at) = at {
+                    e.description(format!("As of <t:{}:f>", at.unix_timestamp()));
+                }
+                e.footer(|f| f.text(id))
+                    .fields(fields.into_iter().map(|(name, value)| (name, value, true)))
+            })
+        })
+        .clone())
+}
+
+pub async fn force_close(
+    api: &dyn DiscordApi,
+    command: &ApplicationCommandInteraction,
+) -> Result<()> {
+    if !staff::can(&command.member, StaffAction::ForceClose)? {
+        return Err(Error::Permission(String::from(
+            "Only staff can force-close a bounty",
+        )));
+    }
+
+    let options = &command.data.options[0].options;
+    let reason = options
+        .iter()
+        .find_map(|arg| match &arg.resolved {
+            Some(CommandDataOptionValue::String(reason)) if arg.name == "reason" => {
+                Some(reason.clone())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| String::from("No reason given"));
+
+    let (id, _) = bounty_reference(options)?;
+    store::read(|store| require_any_status(store, &id, FORCE_CLOSABLE, "force-closed"))?;
+
+    let mut audit_events = Vec::new();
+    respond_slowly(
+        api,
+        command,
+        "Closing bounty…",
+        true,
+        close(api, command, id, &reason, &mut audit_events),
+    )
+    .await?;
+    audit::mirror(api, &audit_events).await;
+    Ok(())
+}
+
+/// Closes the bounty, collecting the audit events it saved in `audit_events`.
+async fn close(
+    api: &dyn DiscordApi,
+    command: &ApplicationCommandInteraction,
+    id: Uuid,
+    reason: &str,
+    audit_events: &mut Vec<AuditEvent>,
+) -> Result<EditInteractionResponse> {
+    let (bounty, refunded) = store::write(|store| -> Result<_> {
+        let bounty = require_any_status(store, &id, FORCE_CLOSABLE, "force-closed")?;
+        store.append(BountyEvent::new(id, EventKind::Cancelled));
+        let refunded = store.ledger.refund(id, bounty.lister.id);
+        let mut event = AuditEvent::new(
+            id,
+            bounty.bounty_number,
+            AuditAction::ForceClosed,
+            Some(command.user.id),
+        )
+        .detail(format!("Reason: {}", reason));
+        event.channel_id = bounty.channel_id;
+        store.audit.push(event.clone());
+        audit_events.push(event);
+        Ok((bounty, refunded))
+    })??;
+
+    if let Some(channel) = bounty.channel_id {
+        let guild_id = in_guild(command.guild_id)?;
+        let notice = format!(
+            "This bounty was closed by {}. Reason: {}",
+            command.user.name, reason
+        );
+        let mut message = CreateMessage::default();
+        message.content(notice);
+        if let Err(err) = api.send_message(channel, message).await {
+            warn!(error = %err, "Could not post force-close notice");
+        }
+
+        let category_name = required_var("ARCHIVE_CATEGORY")?;
+        let mut archived = Vec::new();
+        archived.extend(
+            discord_util::channel::switch_category(api, guild_id, channel, &category_name).await?,
+        );
+        archived.extend(discord_util::channel::convert_to_read_only(api, guild_id, channel).await?);
+        audit::save(&archived);
+        audit_events.extend(archived);
+    }
+
+    let refund = refunded
+        .map(|(amount, currency)| {
+            format!(
+                " Refunded {} {} to {}.",
+                amount, currency, bounty.lister.name
+            )
+        })
+        .unwrap_or_default();
+    let mut response = EditInteractionResponse::default();
+    response.content(format!(
+        "Bounty #{} was force-closed.{}",
+        bounty.bounty_number, refund
+    ));
+    Ok(response)
+}
+
+/// The bounty named by the `bounty` option of a staff command.
+pub fn bounty_reference(options: &[CommandDataOption]) -> Result<(Uuid, Bounty)> {
+    options
+        .iter()
+        .find_map(|arg| match &arg.resolved {
+            Some(CommandDataOptionValue::String(reference)) if arg.name == "bounty" => {
+                autocomplete::resolve(reference)
+            }
+            _ => None,
+        })
+        .inspect(|(id, _)| logging::record_bounty(*id))
+        .ok_or_else(|| Error::Validation(String::from("Bounty not found")))
+}
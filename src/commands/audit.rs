@@ -0,0 +1,170 @@
+use serenity::{
+    builder::{CreateApplicationCommandOption, CreateInteractionResponse, CreateMessage},
+    model::prelude::{
+        command::CommandOptionType,
+        interaction::{
+            application_command::ApplicationCommandInteraction, InteractionResponseType,
+            MessageFlags,
+        },
+    },
+};
+
+use tracing::{error, info, warn};
+
+use crate::commands::{
+    admin::bounty_reference,
+    registry::{respond, Registry},
+    staff,
+};
+use crate::discord_util::{api::DiscordApi, channel::staff_log_channel};
+use crate::error::{Error, Result};
+use crate::storage::{
+    audit::{self, AuditEvent},
+    store,
+};
+
+/// Discord rejects embed descriptions longer than this.
+const MAX_DESCRIPTION: usize = 4096;
+
+pub fn register_routes(registry: &mut Registry) {
+    registry.subcommand("bounty", "audit", register_audit, |api, command| {
+        Box::pin(async move { respond(api, command, run(command)?).await })
+    });
+}
+
+pub fn register_audit(
+    option: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    option
+        .name("audit")
+        .description("Show who did what to a bounty (staff only)")
+        .kind(CommandOptionType::SubCommand)
+        .create_sub_option(|option| {
+            option
+                .name("bounty")
+                .description("The bounty number, or search by participant")
+                .kind(CommandOptionType::String)
+                .set_autocomplete(true)
+                .required(true)
+        })
+}
+
+/// Persists `events` and mirrors them to the staff log channel, for changes that come with
+/// no bounty state to save them with. A bounty action has already happened by the time it is
+/// audited, so failures are only logged.
+pub async fn record(api: &dyn DiscordApi, events: Vec<AuditEvent>) {
+    save(&events);
+    mirror(api, &events).await;
+}
+
+/// Persists `events` on their own. Events that belong to a change of the bounty are pushed in
+/// the same `store::write` instead, so the change and its audit trail are saved together.
+pub fn save(events: &[AuditEvent]) {
+    if events.is_empty() {
+        return;
+    }
+    if let Err(err) = store::write(|store| store.audit.extend(events.iter().cloned())) {
+        error!(error = %err, "Could not save audit events");
+    }
+}
+
+/// Logs saved `events` and posts them to the staff log channel. Call it after responding to
+/// the interaction, as the user does not need to wait for it.
+pub async fn mirror(api: &dyn DiscordApi, events: &[AuditEvent]) {
+    for event in events {
+        info!(
+            bounty_id = %event.bounty_id,
+            bounty_number = event.bounty_number,
+            actor = event.actor.map(|actor| actor.0),
+            channel = event.channel_id.map(|channel| channel.0),
+            detail = event.detail.as_deref(),
+            "Bounty {}",
+            event.action.as_str()
+        );
+    }
+
+    let channel = match staff_log_channel() {
+        Ok(Some(channel)) => channel,
+        Ok(None) => return,
+        Err(err) => {
+            warn!(error = %err, "Could not mirror audit events");
+            return;
+        }
+    };
+    for event in events {
+        let mut message = CreateMessage::default();
+        message.embed(|e| {
+            e.title(format!(
+                "Bounty #{} {}",
+                event.bounty_number,
+                event.action.as_str()
+            ))
+            .description(describe(event))
+            .footer(|f| f.text(event.bounty_id))
+            .timestamp(event.timestamp)
+        });
+        if let Err(err) = api.send_message(channel, message).await {
+            warn!(error = %err, "Could not mirror audit event");
+        }
+    }
+}
+
+pub fn run<'a>(command: &ApplicationCommandInteraction) -> Result<CreateInteractionResponse<'a>> {
+    if !staff::is_staff(&command.member)? {
+        return Err(Error::Permission(String::from(
+            "Only staff can audit bounties",
+        )));
+    }
+
+    let (id, bounty) = bounty_reference(&command.data.options[0].options)?;
+    let lines: Vec<String> = store::read(|store| {
+        audit::timeline(&store.audit, &id)
+            .into_iter()
+            .map(|event| {
+                format!(
+                    "<t:{}:f> **{}** {}",
+                    event.timestamp.unix_timestamp(),
+                    event.action.as_str(),
+                    describe(event).replace('\n', " — ")
+                )
+            })
+            .collect()
+    });
+
+    // Keep the newest events when the timeline does not fit.
+    let mut description = String::new();
+    for line in lines.iter().rev() {
+        if description.len() + line.len() + 1 > MAX_DESCRIPTION {
+            break;
+        }
+        description.insert_str(0, &format!("{}\n", line));
+    }
+    if description.is_empty() {
+        description.push_str("Nothing was recorded for this bounty.");
+    }
+
+    Ok(CreateInteractionResponse::default()
+        .kind(InteractionResponseType::ChannelMessageWithSource)
+        .interaction_response_data(|d| {
+            d.flags(MessageFlags::EPHEMERAL).embed(|e| {
+                e.title(format!("Audit of bounty #{}", bounty.bounty_number))
+                    .description(description)
+                    .footer(|f| f.text(id))
+            })
+        })
+        .clone())
+}
+
+fn describe(event: &AuditEvent) -> String {
+    let mut lines = vec![match event.actor {
+        Some(actor) => format!("By <@{}>", actor),
+        None => String::from("By the bot"),
+    }];
+    if let Some(channel) = event.channel_id {
+        lines.push(format!("In <#{}>", channel));
+    }
+    if let Some(detail) = &event.detail {
+        lines.push(detail.clone());
+    }
+    lines.join("\n")
+}
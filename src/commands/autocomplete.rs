@@ -0,0 +1,168 @@
+use serenity::{
+    builder::CreateAutocompleteResponse,
+    model::{
+        id::UserId,
+        prelude::interaction::{
+            application_command::CommandDataOption, autocomplete::AutocompleteInteraction,
+        },
+    },
+};
+use uuid::Uuid;
+
+use crate::commands::{
+    bounty::{Bounty, BountyStatus},
+    registry::Registry,
+    staff,
+};
+use crate::discord_util::api::DiscordApi;
+use crate::error::Result;
+use crate::storage::store::{self, Store};
+
+const MAX_CHOICES: usize = 25;
+
+pub fn register_routes(registry: &mut Registry) {
+    registry.autocomplete("bounty", |api, autocomplete| {
+        Box::pin(run(api, autocomplete))
+    });
+}
+
+/// Suggests open bounties matching what the user typed so far, by number or participant name.
+pub async fn run(api: &dyn DiscordApi, autocomplete: &AutocompleteInteraction) -> Result<()> {
+    let input = focused_value(&autocomplete.data.options)
+        .unwrap_or_default()
+        .trim()
+        .trim_start_matches('#')
+        .to_lowercase();
+    let staff = staff::is_staff(&autocomplete.member)?;
+    let user = autocomplete.user.id;
+
+    let matches = store::read(|store| suggestions(store, &input, user, staff));
+
+    let mut response = CreateAutocompleteResponse::default();
+    for (id, bounty) in matches.iter().take(MAX_CHOICES) {
+        response.add_string_choice(
+            format!(
+                "#{} {} → {} ({})",
+                bounty.bounty_number,
+                bounty.lister.name,
+                bounty.hunter.name,
+                bounty.status.as_str()
+            ),
+            id,
+        );
+    }
+    api.autocomplete(autocomplete.id, &autocomplete.token, response)
+        .await
+}
+
+/// Open bounties `user` may act on whose number starts with `input` or whose participants'
+/// names contain it, by number.
+fn suggestions(store: &Store, input: &str, user: UserId, staff: bool) -> Vec<(Uuid, Bounty)> {
+    let mut matches: Vec<(Uuid, Bounty)> = store
+        .bounties
+        .iter()
+        .filter(|(_, b)| {
+            matches!(
+                b.status,
+                BountyStatus::Pending
+                    | BountyStatus::Confirming
+                    | BountyStatus::Confirmed
+                    | BountyStatus::Accepted
+            )
+        })
+        .filter(|(_, b)| staff || b.lister.id == user || b.hunter.id == user)
+        .filter(|(_, b)| {
+            b.bounty_number.to_string().starts_with(input)
+                || b.lister.name.to_lowercase().contains(input)
+                || b.hunter.name.to_lowercase().contains(input)
+        })
+        .map(|(id, b)| (*id, b.clone()))
+        .collect();
+    matches.sort_by_key(|(_, b)| b.bounty_number);
+    matches
+}
+
+/// Looks up a bounty reference as produced by the suggestions, falling back to a bounty number.
+pub fn resolve(reference: &str) -> Option<(Uuid, Bounty)> {
+    if let Ok(id) = Uuid::parse_str(reference) {
+        return store::get_bounty(&id).map(|bounty| (id, bounty));
+    }
+
+    store::find_by_number(reference.trim().trim_start_matches('#').parse().ok()?)
+}
+
+fn focused_value(options: &[CommandDataOption]) -> Option<String> {
+    options.iter().find_map(|option| {
+        if option.focused {
+            option.value.as_ref().map(|value| match value.as_str() {
+                Some(value) => value.to_string(),
+                None => value.to_string(),
+            })
+        } else {
+            focused_value(&option.options)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::model::user::User;
+
+    use super::*;
+
+    const LISTER: UserId = UserId(1);
+    const HUNTER: UserId = UserId(2);
+    const OTHER: UserId = UserId(3);
+
+    fn store() -> Store {
+        let user = |id: UserId, name: &str| {
+            let mut user = User::default();
+            user.id = id;
+            user.name = name.to_string();
+            user
+        };
+        let mut store = Store::default();
+        for (number, status, hunter) in [
+            (12, BountyStatus::Accepted, user(HUNTER, "Hunter")),
+            (3, BountyStatus::Pending, user(HUNTER, "Hunter")),
+            (1, BountyStatus::Completed, user(HUNTER, "Hunter")),
+            (14, BountyStatus::Confirmed, user(OTHER, "Someone")),
+        ] {
+            let mut bounty = Bounty::new(
+                user(LISTER, "Lister"),
+                hunter,
+                number,
+                0,
+                String::from("USD"),
+            );
+            bounty.status = status;
+            store.bounties.insert(Uuid::new_v4(), bounty);
+        }
+        store
+    }
+
+    fn numbers(store: &Store, input: &str, user: UserId, staff: bool) -> Vec<u32> {
+        suggestions(store, input, user, staff)
+            .iter()
+            .map(|(_, bounty)| bounty.bounty_number)
+            .collect()
+    }
+
+    #[test]
+    fn open_bounties_match_by_number_prefix_or_name() {
+        let store = store();
+        assert_eq!(numbers(&store, "", LISTER, false), vec![3, 12, 14]);
+        assert_eq!(numbers(&store, "1", LISTER, false), vec![12, 14]);
+        assert_eq!(numbers(&store, "some", LISTER, false), vec![14]);
+        assert_eq!(numbers(&store, "hunt", LISTER, false), vec![3, 12]);
+        assert!(numbers(&store, "2", LISTER, false).is_empty());
+    }
+
+    #[test]
+    fn only_staff_see_bounties_they_are_not_part_of() {
+        let store = store();
+        assert_eq!(numbers(&store, "", HUNTER, false), vec![3, 12]);
+        assert!(numbers(&store, "", UserId(4), false).is_empty());
+        assert_eq!(numbers(&store, "", UserId(4), true), vec![3, 12, 14]);
+    }
+}
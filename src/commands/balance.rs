@@ -0,0 +1,67 @@
+use serenity::{
+    builder::{CreateApplicationCommand, CreateInteractionResponse},
+    model::{
+        prelude::{
+            command::CommandOptionType,
+            interaction::{
+                application_command::{ApplicationCommandInteraction, CommandDataOptionValue},
+                InteractionResponseType, MessageFlags,
+            },
+        },
+        user::User,
+    },
+};
+
+use crate::commands::registry::{respond, Registry};
+use crate::storage::store;
+
+pub fn register_routes(registry: &mut Registry) {
+    registry.command("balance", register, |api, command| {
+        Box::pin(respond(api, command, run(command)))
+    });
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("balance")
+        .description("Show the bounty reward balance of a user")
+        .create_option(|option| {
+            option
+                .name("user")
+                .description("The user to look up (defaults to you)")
+                .kind(CommandOptionType::User)
+        })
+}
+
+pub fn run<'a>(command: &ApplicationCommandInteraction) -> CreateInteractionResponse<'a> {
+    let mut user: User = command.user.clone();
+    for arg in &command.data.options {
+        if let Some(CommandDataOptionValue::User(target, _)) = &arg.resolved {
+            user = target.clone();
+        }
+    }
+
+    let (balances, in_escrow) = store::read(|store| {
+        (
+            store.ledger.balances(user.id),
+            store.ledger.in_escrow(user.id),
+        )
+    });
+
+    let mut message = format!("**Balance of {}**\n", user.name);
+    if balances.is_empty() {
+        message.push_str("No rewards recorded yet.");
+    }
+    for (currency, amount) in &balances {
+        message.push_str(&format!("{}: {}", currency, amount));
+        if let Some(held) = in_escrow.get(currency) {
+            message.push_str(&format!(" ({} in escrow)", held));
+        }
+        message.push('\n');
+    }
+
+    CreateInteractionResponse::default()
+        .kind(InteractionResponseType::ChannelMessageWithSource)
+        .interaction_response_data(|d| d.content(message).flags(MessageFlags::EPHEMERAL))
+        .clone()
+}
@@ -1,61 +1,177 @@
-use once_cell::sync::Lazy;
-use std::{collections::HashMap, env};
+use serde::{Deserialize, Serialize};
 
 use serenity::{
-    builder::{CreateApplicationCommand, CreateInteractionResponse},
-    http::Http,
+    builder::{
+        CreateApplicationCommandOption, CreateComponents, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, EditInteractionResponse,
+    },
     model::{
         prelude::{
             command::CommandOptionType,
             component::ButtonStyle,
             interaction::{
-                application_command::{ApplicationCommandInteraction, CommandDataOptionValue},
+                application_command::{
+                    ApplicationCommandInteraction, CommandDataOption, CommandDataOptionValue,
+                },
                 message_component::MessageComponentInteraction,
                 InteractionResponseType, MessageFlags,
             },
+            ChannelId, GuildId, InteractionId, UserId,
         },
         user::User,
+        Timestamp,
     },
-    prelude::Context,
 };
+use tracing::{error, warn};
 use uuid::Uuid;
 
-use crate::discord_util::{self, channel::add_ni_team};
+use crate::commands::{
+    achievements, audit,
+    component_id::ComponentId,
+    rating,
+    registry::{mismatched, respond_slowly, update_slowly, Registry},
+    staff::{self, StaffAction},
+};
+use crate::discord_util::{self, api::DiscordApi, channel::add_staff};
+use crate::error::{required_var, Error, Result};
+use crate::logging;
+use crate::payout::provider::{self, Payout, PayoutStatus};
+use crate::storage::{
+    audit::{AuditAction, AuditEvent},
+    events::{BountyEvent, EventKind},
+    store::{self, Store},
+};
+
+pub const DEFAULT_CURRENCY: &str = "USD";
+/// The largest reward a bounty can hold, for the `reward` option and the bounty forms alike.
+pub const MAX_REWARD: u64 = 1_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BountyStatus {
+    Pending,
+    /// The lister confirmed the bounty and its channel is being created.
+    Confirming,
+    Confirmed,
+    Accepted,
+    Declined,
+    Completed,
+    Cancelled,
+}
+
+impl BountyStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BountyStatus::Pending => "pending",
+            BountyStatus::Confirming => "confirming",
+            BountyStatus::Confirmed => "confirmed",
+            BountyStatus::Accepted => "accepted",
+            BountyStatus::Declined => "declined",
+            BountyStatus::Completed => "completed",
+            BountyStatus::Cancelled => "cancelled",
+        }
+    }
 
-static mut ACTIVE_BOUNTIES: Lazy<HashMap<Uuid, Bounty>> = Lazy::new(|| {
-    let map = HashMap::new();
-    map
-});
+    pub fn parse(value: &str) -> Option<BountyStatus> {
+        match value {
+            "pending" => Some(BountyStatus::Pending),
+            "confirming" => Some(BountyStatus::Confirming),
+            "confirmed" => Some(BountyStatus::Confirmed),
+            "accepted" => Some(BountyStatus::Accepted),
+            "declined" => Some(BountyStatus::Declined),
+            "completed" => Some(BountyStatus::Completed),
+            "cancelled" => Some(BountyStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bounty {
     pub lister: User,
     pub hunter: User,
     pub bounty_number: u32,
+    pub reward: u64,
+    pub currency: String,
+    pub status: BountyStatus,
+    pub channel_id: Option<ChannelId>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub payout: Option<Payout>,
+    #[serde(default)]
+    pub created_at: Option<Timestamp>,
+    #[serde(default)]
+    pub accepted_at: Option<Timestamp>,
+    #[serde(default)]
+    pub completed_at: Option<Timestamp>,
 }
 
 impl Bounty {
-    pub fn new(lister: User, hunter: User, bounty_number: u32) -> Bounty {
+    pub fn new(
+        lister: User,
+        hunter: User,
+        bounty_number: u32,
+        reward: u64,
+        currency: String,
+    ) -> Bounty {
         Bounty {
             lister: lister.clone(),
             hunter,
             bounty_number,
+            reward,
+            currency,
+            status: BountyStatus::Pending,
+            channel_id: None,
+            description: None,
+            payout: None,
+            created_at: Some(Timestamp::now()),
+            accepted_at: None,
+            completed_at: None,
         }
     }
 }
 
-pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
-    command
-        .name("bounty")
+pub fn register_routes(registry: &mut Registry) {
+    registry.group("bounty", "Manage bounties");
+    registry.subcommand("bounty", "start", register_start, |api, command| {
+        Box::pin(run(api, command))
+    });
+    registry.subcommand("bounty", "reopen", register_reopen, |api, command| {
+        Box::pin(reopen(api, command))
+    });
+    registry.component("confirm", |api, component, id| match id {
+        ComponentId::Confirm(bounty_id) => Box::pin(confirm(api, component, bounty_id)),
+        other => mismatched(other),
+    });
+    registry.component("accept", |api, component, id| match id {
+        ComponentId::Accept(bounty_id) => Box::pin(accept(api, component, bounty_id)),
+        other => mismatched(other),
+    });
+    registry.component("decline", |api, component, id| match id {
+        ComponentId::Decline(bounty_id) => Box::pin(decline(api, component, bounty_id)),
+        other => mismatched(other),
+    });
+    registry.component("complete", |api, component, id| match id {
+        ComponentId::Complete(bounty_id) => Box::pin(complete(api, component, bounty_id)),
+        other => mismatched(other),
+    });
+}
+
+pub fn register_start(
+    option: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    option
+        .name("start")
         .description("Start a bounty with the specified bounty hunter")
-        .create_option(|option| {
+        .kind(CommandOptionType::SubCommand)
+        .create_sub_option(|option| {
             option
                 .name("hunter")
                 .description("The bounty hunter")
                 .kind(CommandOptionType::User)
                 .required(true)
         })
-        .create_option(|option| {
+        .create_sub_option(|option| {
             option
                 .name("bounty-number")
                 .description("The bounty number")
@@ -63,196 +179,655 @@ pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicatio
                 .min_int_value(1)
                 .required(true)
         })
+        .create_sub_option(|option| {
+            option
+                .name("reward")
+                .description("The reward held in escrow until the bounty is completed")
+                .kind(CommandOptionType::Integer)
+                .min_int_value(0)
+                .max_int_value(MAX_REWARD)
+        })
+        .create_sub_option(|option| {
+            option
+                .name("currency")
+                .description("The currency of the reward (defaults to USD)")
+                .kind(CommandOptionType::String)
+        })
 }
 
-pub fn run<'a>(command: &ApplicationCommandInteraction) -> CreateInteractionResponse<'a> {
-    let message: String = "Please confirm the bounty".to_string();
-    let id = Uuid::new_v4();
+pub fn register_reopen(
+    option: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    option
+        .name("reopen")
+        .description("Reopen the archived bounty in this channel (staff only)")
+        .kind(CommandOptionType::SubCommand)
+}
 
-    let (hunter, number) = extract_command_args(command.clone());
+pub async fn run(api: &dyn DiscordApi, command: &ApplicationCommandInteraction) -> Result<()> {
+    let args = extract_command_args(&command.data.options[0].options)?;
 
-    let new_bounty = Bounty::new(command.user.clone(), hunter, number);
-    unsafe {
-        ACTIVE_BOUNTIES.insert(id, new_bounty);
-    }
+    let new_bounty = Bounty::new(
+        command.user.clone(),
+        args.hunter,
+        args.number,
+        args.reward,
+        args.currency,
+    );
 
-    CreateInteractionResponse::default()
+    start(
+        api,
+        (command.id, &command.token),
+        new_bounty,
+        command.user.id,
+        "Please confirm the bounty",
+        true,
+    )
+    .await
+}
+
+/// Stores a new bounty started by `actor` and asks its lister to confirm it, in response to
+/// the `interaction` with this ID and token.
+pub async fn start(
+    api: &dyn DiscordApi,
+    (interaction_id, token): (InteractionId, &str),
+    bounty: Bounty,
+    actor: UserId,
+    message: &str,
+    ephemeral: bool,
+) -> Result<()> {
+    let id = Uuid::new_v4();
+    logging::record_bounty(id);
+    let custom_id = ComponentId::Confirm(id).encode()?;
+    let event = AuditEvent::new(id, bounty.bounty_number, AuditAction::Started, Some(actor))
+        .detail(format!(
+            "{} → {}, reward {} {}",
+            bounty.lister.name, bounty.hunter.name, bounty.reward, bounty.currency
+        ));
+    store::write(|store| {
+        store.append(BountyEvent::new(
+            id,
+            EventKind::Created {
+                bounty: Box::new(bounty),
+            },
+        ));
+        store.audit.push(event.clone());
+    })?;
+
+    let response = CreateInteractionResponse::default()
         .kind(InteractionResponseType::ChannelMessageWithSource)
         .interaction_response_data(|d| {
+            if ephemeral {
+                d.flags(MessageFlags::EPHEMERAL);
+            }
             d.content(message)
-                .flags(MessageFlags::EPHEMERAL)
-                .components(|c| {
-                    c.create_action_row(|row| {
-                        row.create_button(|b| {
-                            b.style(ButtonStyle::Success)
-                                .label("Confirm Bounty")
-                                .custom_id(String::from("bounty/") + id.to_string().as_str())
-                        })
-                    })
-                })
+                .components(|c| action_button(c, "Confirm Bounty", &custom_id))
         })
-        .clone()
+        .clone();
+    api.respond(interaction_id, token, response).await?;
+    audit::mirror(api, &[event]).await;
+    Ok(())
 }
 
-pub async fn confirm_bounty(
-    http: &Http,
+pub async fn confirm(
+    api: &dyn DiscordApi,
     component: &MessageComponentInteraction,
-) -> Result<(), String> {
-    let ind = component.data.custom_id.find('/').unwrap();
-    let id = &component.data.custom_id[ind + 1..];
-
-    let curr_bounty;
-    unsafe {
-        curr_bounty = ACTIVE_BOUNTIES.get(&Uuid::parse_str(id).unwrap());
+    bounty_id: Uuid,
+) -> Result<()> {
+    let bounty = store::get_bounty(&bounty_id).ok_or_else(not_found)?;
+    if component.user != bounty.lister {
+        return Err(Error::Permission(String::from(
+            "Only the bounty lister can confirm the bounty",
+        )));
     }
 
-    match curr_bounty {
-        Some(bounty) => {
-            if component.user != bounty.lister {
-                return Err(String::from(
-                    "Only the bounty lister can confirm the command",
-                ));
-            }
+    let custom_id = ComponentId::Confirm(bounty_id).encode()?;
+    let mut retry = EditInteractionResponse::default();
+    retry
+        .content(&component.message.content)
+        .components(|c| action_button(c, "Confirm Bounty", &custom_id));
 
-            if let Err(err) = discord_util::channel::create_private_text_channel(
-                http,
-                component.guild_id.unwrap(),
-                "BOUNTY PLATFORM",
-                bounty,
-                id,
-            )
-            .await
-            {
-                eprintln!("Could not create channel: {}", err);
-            }
-        }
-        None => {
-            return Err(String::from("Not found"));
+    // Claimed under the lock before Discord is called, so a second click cannot create a
+    // second channel.
+    store::write(|store| -> Result<_> {
+        require_status(store, &bounty_id, BountyStatus::Pending, "confirmed")?;
+        store.append(BountyEvent::new(bounty_id, EventKind::Confirming));
+        Ok(())
+    })??;
+
+    let mut audit_events = Vec::new();
+    let confirmed = update_slowly(api, component, "Creating channel…", retry, async {
+        let channel_id;
+        (channel_id, audit_events) = confirm_bounty(api, component, bounty_id, &bounty).await?;
+        let mut response = EditInteractionResponse::default();
+        response.content(format!(
+            "Confirmed, the bounty continues in <#{}>",
+            channel_id
+        ));
+        Ok(response)
+    })
+    .await;
+    match confirmed {
+        Ok(()) => audit::mirror(api, &audit_events).await,
+        Err(_) => release_claim(bounty_id),
+    }
+    confirmed
+}
+
+/// Hands a bounty whose channel was never linked back to the lister to confirm again. Does
+/// nothing once the bounty is confirmed.
+pub fn release_claim(bounty_id: Uuid) {
+    let released = store::write(|store| {
+        let confirming = store
+            .bounties
+            .get(&bounty_id)
+            .is_some_and(|bounty| bounty.status == BountyStatus::Confirming);
+        if confirming {
+            store.append(BountyEvent::new(bounty_id, EventKind::ConfirmationFailed));
         }
+    });
+    if let Err(err) = released {
+        error!(error = %err, "Could not release bounty {}", bounty_id);
     }
+}
 
-    Ok(())
+/// Creates the private channel of a bounty claimed for confirmation and holds its reward in
+/// escrow, returning the channel and the audit events saved with it.
+pub async fn confirm_bounty(
+    api: &dyn DiscordApi,
+    component: &MessageComponentInteraction,
+    bounty_id: Uuid,
+    bounty: &Bounty,
+) -> Result<(ChannelId, Vec<AuditEvent>)> {
+    let category_name = required_var("BOUNTY_CATEGORY")?;
+    let (channel_id, created) = discord_util::channel::create_private_text_channel(
+        api,
+        in_guild(component.guild_id)?,
+        &category_name,
+        bounty,
+        bounty_id,
+    )
+    .await?;
+
+    let events = vec![
+        created,
+        AuditEvent::new(
+            bounty_id,
+            bounty.bounty_number,
+            AuditAction::Confirmed,
+            Some(component.user.id),
+        )
+        .channel(channel_id),
+    ];
+    record_confirmation(bounty_id, channel_id, &events)?;
+    Ok((channel_id, events))
 }
 
-pub async fn accept(http: &Http, component: &MessageComponentInteraction, id: &str) {
-    let curr_bounty;
-    unsafe {
-        curr_bounty = ACTIVE_BOUNTIES.get(&Uuid::parse_str(id).unwrap()).unwrap();
-    }
+/// Links a bounty to its new channel and holds its reward in escrow, saving `audit` with it.
+/// Bounties that are already linked are refused, so the reward is never held twice.
+pub fn record_confirmation(
+    bounty_id: Uuid,
+    channel_id: ChannelId,
+    audit: &[AuditEvent],
+) -> Result<()> {
+    store::write(|store| {
+        let bounty = store
+            .bounties
+            .get(&bounty_id)
+            .cloned()
+            .ok_or_else(not_found)?;
+        if !matches!(
+            bounty.status,
+            BountyStatus::Pending | BountyStatus::Confirming
+        ) {
+            return Err(Error::Validation(String::from(
+                "This bounty is already confirmed",
+            )));
+        }
+        store.append(BountyEvent::new(
+            bounty_id,
+            EventKind::Confirmed { channel_id },
+        ));
+        store
+            .ledger
+            .hold(bounty_id, bounty.lister.id, bounty.reward, &bounty.currency);
+        store.audit.extend(audit.iter().cloned());
+        Ok(())
+    })?
+}
 
+pub async fn accept(
+    api: &dyn DiscordApi,
+    component: &MessageComponentInteraction,
+    bounty_id: Uuid,
+) -> Result<()> {
+    let curr_bounty = store::get_bounty(&bounty_id).ok_or_else(not_found)?;
     if component.user != curr_bounty.hunter {
-        let message = "Only the bounty hunter can accept the bounty";
-
-        let _ = component
-            .create_interaction_response(http, |r| {
-                r.kind(InteractionResponseType::ChannelMessageWithSource)
-                    .interaction_response_data(|d| {
-                        d.content(message).flags(MessageFlags::EPHEMERAL)
-                    })
-            })
-            .await;
-    } else {
-        if let Err(err) = component
-            .create_interaction_response(http, |r| {
-                r.kind(InteractionResponseType::UpdateMessage)
-                    .interaction_response_data(|d| d.content("Accepted").components(|c| c))
-            })
-            .await
-        {
-            eprintln!("Failed to accept bounty: {:?}", err);
-        }
-        add_ni_team(http, component.guild_id.unwrap(), component.channel_id).await;
-
-        let message = "Please complete the bounty when the task is done.";
-
-        let _ = component
-            .create_followup_message(http, |m| {
-                m.content(message).components(|c| {
-                    c.create_action_row(|r| {
-                        r.create_button(|b| {
-                            b.style(ButtonStyle::Success)
-                                .label("Complete Bounty")
-                                .custom_id(String::from("Complete/") + id)
-                        })
-                    })
-                })
-            })
-            .await;
+        return Err(Error::Permission(String::from(
+            "Only the bounty hunter can accept the bounty",
+        )));
     }
+    let complete_id = ComponentId::Complete(bounty_id).encode()?;
+    let event = AuditEvent::new(
+        bounty_id,
+        curr_bounty.bounty_number,
+        AuditAction::Accepted,
+        Some(component.user.id),
+    )
+    .channel(component.channel_id);
+
+    store::write(|store| -> Result<_> {
+        require_status(store, &bounty_id, BountyStatus::Confirmed, "accepted")?;
+        store.append(BountyEvent::new(bounty_id, EventKind::Accepted));
+        store.audit.push(event.clone());
+        Ok(())
+    })??;
+    api.respond(component.id, &component.token, update_message("Accepted"))
+        .await?;
+    audit::mirror(api, &[event]).await;
+    if let Some(event) = add_staff(api, component.channel_id).await? {
+        audit::record(api, vec![event]).await;
+    }
+
+    let message = "Please complete the bounty when the task is done.";
+
+    let mut followup = CreateInteractionResponseFollowup::default();
+    followup
+        .content(message)
+        .components(|c| action_button(c, "Complete Bounty", &complete_id));
+    api.follow_up(&component.token, followup).await
+}
+
+struct BountyArgs {
+    hunter: User,
+    number: u32,
+    reward: u64,
+    currency: String,
 }
 
-fn extract_command_args(input: ApplicationCommandInteraction) -> (User, u32) {
+fn extract_command_args(options: &[CommandDataOption]) -> Result<BountyArgs> {
     let mut hunter: User = User::default();
     let mut number: u32 = 0;
-    for arg in input.data.options {
+    let mut reward: u64 = 0;
+    let mut currency = String::from(DEFAULT_CURRENCY);
+    for arg in options {
         match arg.name.as_str() {
             "hunter" => {
-                if let Some(arg) = arg.resolved {
+                if let Some(arg) = &arg.resolved {
                     if let CommandDataOptionValue::User(user, _) = arg {
                         hunter = user.clone();
                     }
                 }
             }
             "bounty-number" => {
-                if let Some(arg) = arg.resolved {
+                if let Some(arg) = &arg.resolved {
                     if let CommandDataOptionValue::Integer(val) = arg {
-                        if val <= u32::MAX as i64 {
-                            number = val as u32;
+                        if *val <= u32::MAX as i64 {
+                            number = *val as u32;
                         } else {
-                            eprintln!("Invalid bounty number");
+                            warn!(value = val, "Invalid bounty number");
                         }
                     }
                 }
             }
+            "reward" => {
+                if let Some(CommandDataOptionValue::Integer(val)) = &arg.resolved {
+                    reward = (*val).max(0) as u64;
+                }
+            }
+            "currency" => {
+                if let Some(CommandDataOptionValue::String(val)) = &arg.resolved {
+                    currency = parse_currency(val)?;
+                }
+            }
             _ => {
-                eprintln!("Unknown argument");
+                warn!(option = %arg.name, "Unknown argument");
             }
         }
     }
 
-    (hunter, number)
+    Ok(BountyArgs {
+        hunter,
+        number,
+        reward,
+        currency,
+    })
 }
 
-pub async fn decline(http: &Http, component: &MessageComponentInteraction) {
-    if let Err(err) = component
-        .create_interaction_response(http, |r| {
-            r.kind(InteractionResponseType::UpdateMessage)
-                .interaction_response_data(|d| d.content("Declined").components(|c| c))
-        })
-        .await
+/// Normalizes a currency code given by a user, which must be 1 to 10 letters or digits.
+pub fn parse_currency(currency: &str) -> Result<String> {
+    let currency = currency.trim();
+    if currency.is_empty()
+        || currency.len() > 10
+        || !currency.chars().all(|c| c.is_ascii_alphanumeric())
     {
-        eprintln!("Failed to decline bounty: {:?}", err);
+        return Err(Error::Validation(String::from(
+            "Currencies must be 1 to 10 letters or digits",
+        )));
     }
+
+    Ok(currency.to_uppercase())
 }
 
-pub async fn complete(ctx: &Context, component: &MessageComponentInteraction) {
-    let ni_role = env::var("NI_ROLE").expect("NI Team role name not set.");
+pub async fn decline(
+    api: &dyn DiscordApi,
+    component: &MessageComponentInteraction,
+    bounty_id: Uuid,
+) -> Result<()> {
+    let bounty = store::get_bounty(&bounty_id).ok_or_else(not_found)?;
+    if component.user != bounty.hunter && !staff::can(&component.member, StaffAction::Cancel)? {
+        return Err(Error::Permission(String::from(
+            "Only the bounty hunter can decline the bounty",
+        )));
+    }
 
-    if let Some(member) = &component.member {
-        if member.roles.iter().any(|r| {
-            if let Some(role) = r.to_role_cached(&ctx.cache) {
-                role.name == ni_role
-            } else {
-                false
-            }
-        }) {
-            if let Err(err) = component
-                .create_interaction_response(&ctx.http, |r| {
-                    r.kind(InteractionResponseType::UpdateMessage)
-                        .interaction_response_data(|d| d.content("Completed").components(|c| c))
-                })
-                .await
-            {
-                eprintln!("Failed to complete bounty: {:?}", err);
-            }
+    let mut event = AuditEvent::new(
+        bounty_id,
+        bounty.bounty_number,
+        AuditAction::Declined,
+        Some(component.user.id),
+    )
+    .channel(component.channel_id);
+    if component.user != bounty.hunter {
+        event = event.detail("Declined by staff on behalf of the hunter");
+    }
+
+    store::write(|store| -> Result<_> {
+        let bounty = require_status(store, &bounty_id, BountyStatus::Confirmed, "declined")?;
+        store.append(BountyEvent::new(bounty_id, EventKind::Declined));
+        store.ledger.refund(bounty_id, bounty.lister.id);
+        store.audit.push(event.clone());
+        Ok(())
+    })??;
+    api.respond(component.id, &component.token, update_message("Declined"))
+        .await?;
+    audit::mirror(api, &[event]).await;
+    Ok(())
+}
+
+pub async fn complete(
+    api: &dyn DiscordApi,
+    component: &MessageComponentInteraction,
+    bounty_id: Uuid,
+) -> Result<()> {
+    if !staff::can(&component.member, StaffAction::Complete)? {
+        return Err(Error::Permission(String::from(
+            "Only staff can complete a bounty",
+        )));
+    }
+    let bounty = store::read(|store| {
+        require_status(store, &bounty_id, BountyStatus::Accepted, "completed")
+    })?;
+    let guild_id = in_guild(component.guild_id)?;
+    let category_name = required_var("ARCHIVE_CATEGORY")?;
 
-            discord_util::channel::switch_category(ctx, component, "ARCHIVES").await;
-            discord_util::channel::convert_to_read_only(
-                &ctx.http,
-                component.guild_id.unwrap(),
+    let custom_id = ComponentId::Complete(bounty_id).encode()?;
+    let mut retry = EditInteractionResponse::default();
+    retry
+        .content(&component.message.content)
+        .components(|c| action_button(c, "Complete Bounty", &custom_id));
+
+    let mut released = None;
+    let mut audit_events = Vec::new();
+    update_slowly(api, component, "Archiving channel…", retry, async {
+        audit_events.extend(
+            discord_util::channel::switch_category(
+                api,
+                guild_id,
                 component.channel_id,
+                &category_name,
+            )
+            .await?,
+        );
+        audit_events.extend(
+            discord_util::channel::convert_to_read_only(api, guild_id, component.channel_id)
+                .await?,
+        );
+        audit_events.push(
+            AuditEvent::new(
+                bounty_id,
+                bounty.bounty_number,
+                AuditAction::Completed,
+                Some(component.user.id),
             )
-            .await;
+            .channel(component.channel_id),
+        );
+
+        // Checked again under the lock: another click may have completed it meanwhile.
+        released = store::write(|store| -> Result<_> {
+            let bounty = require_status(store, &bounty_id, BountyStatus::Accepted, "completed")?;
+            store.append(BountyEvent::new(bounty_id, EventKind::Completed));
+            store.audit.extend(audit_events.iter().cloned());
+            Ok(store.ledger.release(bounty_id, bounty.hunter.id))
+        })??;
+
+        let mut response = EditInteractionResponse::default();
+        response.content("Completed");
+        Ok(response)
+    })
+    .await?;
+    audit::mirror(api, &audit_events).await;
+
+    if let Some((amount, currency)) = released {
+        if let Err(err) = pay_hunter(api, component, bounty_id, &bounty, amount, &currency).await {
+            warn!(error = %err, "Could not pay out bounty");
         }
     }
+    if let Err(err) = achievements::award(api, guild_id, bounty_id, &bounty).await {
+        warn!(error = %err, "Could not award achievements");
+    }
+    rating::prompt(api, &bounty_id, &bounty).await;
+
+    Ok(())
+}
+
+async fn pay_hunter(
+    api: &dyn DiscordApi,
+    component: &MessageComponentInteraction,
+    bounty_id: Uuid,
+    bounty: &Bounty,
+    amount: u64,
+    currency: &str,
+) -> Result<()> {
+    let provider = match provider::configured()? {
+        Some(provider) => provider,
+        None => return Ok(()),
+    };
+
+    set_payout(&bounty_id, provider.name(), PayoutStatus::Pending, None)?;
+
+    let message = match provider
+        .pay(bounty_id, bounty, &bounty.hunter, amount, currency)
+        .await
+    {
+        Ok(reference) => {
+            set_payout(
+                &bounty_id,
+                provider.name(),
+                PayoutStatus::Paid,
+                Some(reference.clone()),
+            )?;
+            format!(
+                "Paid {} {} to {} (reference {}).",
+                amount, currency, bounty.hunter.name, reference
+            )
+        }
+        Err(err) => {
+            error!(error = %err, "Payout failed");
+            set_payout(&bounty_id, provider.name(), PayoutStatus::Failed, None)?;
+            format!(
+                "Payout of {} {} to {} failed, staff will settle it manually.",
+                amount, currency, bounty.hunter.name
+            )
+        }
+    };
+
+    let mut followup = CreateInteractionResponseFollowup::default();
+    followup.content(message);
+    api.follow_up(&component.token, followup).await
+}
+
+fn set_payout(
+    id: &Uuid,
+    provider: &str,
+    status: PayoutStatus,
+    reference: Option<String>,
+) -> Result<()> {
+    store::append(BountyEvent::new(
+        *id,
+        EventKind::PayoutUpdated {
+            payout: Payout {
+                provider: provider.to_string(),
+                status,
+                reference,
+            },
+        },
+    ))
+}
+
+pub async fn reopen(api: &dyn DiscordApi, command: &ApplicationCommandInteraction) -> Result<()> {
+    if !staff::can(&command.member, StaffAction::Complete)? {
+        return Err(Error::Permission(String::from(
+            "Only staff can reopen a bounty",
+        )));
+    }
+
+    let archived = store::read(|store| {
+        store
+            .bounties
+            .iter()
+            .find(|(_, bounty)| {
+                bounty.channel_id == Some(command.channel_id)
+                    && bounty.status == BountyStatus::Completed
+            })
+            .map(|(id, bounty)| (*id, bounty.clone()))
+    });
+
+    let (id, bounty) = archived.ok_or_else(|| {
+        Error::Validation(String::from("There is no archived bounty in this channel"))
+    })?;
+    logging::record_bounty(id);
+    let complete_id = ComponentId::Complete(id).encode()?;
+
+    let guild_id = in_guild(command.guild_id)?;
+    let category_name = required_var("BOUNTY_CATEGORY")?;
+
+    let mut audit_events = Vec::new();
+    respond_slowly(api, command, "Reopening bounty…", false, async {
+        audit_events.extend(
+            discord_util::channel::switch_category(
+                api,
+                guild_id,
+                command.channel_id,
+                &category_name,
+            )
+            .await?,
+        );
+        audit_events.extend(
+            discord_util::channel::restore_permissions(api, guild_id, command.channel_id, &bounty)
+                .await?,
+        );
+        audit_events.push(
+            AuditEvent::new(
+                id,
+                bounty.bounty_number,
+                AuditAction::Reopened,
+                Some(command.user.id),
+            )
+            .channel(command.channel_id),
+        );
+        store::write(|store| {
+            store.append(BountyEvent::new(id, EventKind::Reopened));
+            store.audit.extend(audit_events.iter().cloned());
+        })?;
+
+        let mut response = EditInteractionResponse::default();
+        response
+            .content("Bounty reopened. Please complete the bounty when the task is done.")
+            .components(|c| action_button(c, "Complete Bounty", &complete_id));
+        Ok(response)
+    })
+    .await?;
+    audit::mirror(api, &audit_events).await;
+    Ok(())
+}
+
+fn action_button<'c>(
+    components: &'c mut CreateComponents,
+    label: &str,
+    custom_id: &str,
+) -> &'c mut CreateComponents {
+    components.create_action_row(|r| {
+        r.create_button(|b| {
+            b.style(ButtonStyle::Success)
+                .label(label)
+                .custom_id(custom_id)
+        })
+    })
+}
+
+/// The guild an interaction came from; bounties do not work in direct messages.
+pub fn in_guild(guild_id: Option<GuildId>) -> Result<GuildId> {
+    guild_id.ok_or_else(|| Error::Validation(String::from("Bounties only work inside a server")))
+}
+
+/// The server the bot runs bounties in, for interactions that arrive by direct message.
+pub fn configured_guild() -> Result<GuildId> {
+    required_var("GUILD_ID")?
+        .parse()
+        .map(GuildId)
+        .map_err(|_| Error::Config(String::from("GUILD_ID is not a valid ID")))
+}
+
+fn not_found() -> Error {
+    Error::Validation(String::from("This bounty no longer exists"))
+}
+
+/// The bounty, if it is in the `expected` state. Run it inside `store::write` so two clicks on
+/// the same button cannot both move the bounty along.
+fn require_status(
+    store: &Store,
+    bounty_id: &Uuid,
+    expected: BountyStatus,
+    action: &str,
+) -> Result<Bounty> {
+    require_any_status(store, bounty_id, &[expected], action)
+}
+
+/// The bounty, if it is in one of the `allowed` states. Like `require_status`, run it inside
+/// `store::write`.
+pub(crate) fn require_any_status(
+    store: &Store,
+    bounty_id: &Uuid,
+    allowed: &[BountyStatus],
+    action: &str,
+) -> Result<Bounty> {
+    let bounty = store.bounties.get(bounty_id).ok_or_else(not_found)?;
+    if !allowed.contains(&bounty.status) {
+        let names: Vec<&str> = allowed.iter().map(|status| status.as_str()).collect();
+        let names = match names.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+            _ => names.concat(),
+        };
+        return Err(Error::Validation(format!(
+            "Only {} bounties can be {}",
+            names, action
+        )));
+    }
+    Ok(bounty.clone())
+}
+
+pub fn ephemeral_response<'a>(message: &str) -> CreateInteractionResponse<'a> {
+    CreateInteractionResponse::default()
+        .kind(InteractionResponseType::ChannelMessageWithSource)
+        .interaction_response_data(|d| d.content(message).flags(MessageFlags::EPHEMERAL))
+        .clone()
+}
+
+/// Replaces a component's message with `message`, removing its buttons.
+fn update_message<'a>(message: &str) -> CreateInteractionResponse<'a> {
+    CreateInteractionResponse::default()
+        .kind(InteractionResponseType::UpdateMessage)
+        .interaction_response_data(|d| d.content(message).components(|c| c))
+        .clone()
 }
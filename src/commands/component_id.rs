@@ -0,0 +1,322 @@
+use std::fmt;
+
+use serenity::model::id::UserId;
+use uuid::Uuid;
+
+use crate::commands::leaderboard::{Metric, Period};
+
+/// Discord rejects custom IDs longer than this.
+pub const MAX_LENGTH: usize = 100;
+
+const VERSION: &str = "v1";
+const SEPARATOR: char = ':';
+
+/// The custom ID of every button, select menu and modal the bot sends.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComponentId {
+    Confirm(Uuid),
+    Accept(Uuid),
+    Decline(Uuid),
+    Complete(Uuid),
+    Rate(Uuid),
+    RatingForm {
+        bounty: Uuid,
+        score: u8,
+    },
+    Leaderboard {
+        period: Period,
+        metric: Metric,
+        currency: String,
+        page: usize,
+    },
+    /// Form of the "Start bounty with…" context menu, carrying the hunter.
+    StartBountyForm(UserId),
+    /// Form of the "Turn into bounty" context menu, carrying the lister.
+    MessageBountyForm(UserId),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComponentIdError {
+    UnsupportedVersion(String),
+    UnknownKind(String),
+    Malformed(String),
+    TooLong(usize),
+}
+
+impl fmt::Display for ComponentIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentIdError::UnsupportedVersion(version) => {
+                write!(f, "unsupported custom ID version {}", version)
+            }
+            ComponentIdError::UnknownKind(kind) => write!(f, "unknown custom ID kind {}", kind),
+            ComponentIdError::Malformed(id) => write!(f, "malformed custom ID {}", id),
+            ComponentIdError::TooLong(len) => {
+                write!(
+                    f,
+                    "custom ID is {} characters, over the limit of {}",
+                    len, MAX_LENGTH
+                )
+            }
+        }
+    }
+}
+
+impl ComponentId {
+    /// The name handlers are registered under.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ComponentId::Confirm(_) => "confirm",
+            ComponentId::Accept(_) => "accept",
+            ComponentId::Decline(_) => "decline",
+            ComponentId::Complete(_) => "complete",
+            ComponentId::Rate(_) => "rate",
+            ComponentId::RatingForm { .. } => "rating",
+            ComponentId::Leaderboard { .. } => "leaderboard",
+            ComponentId::StartBountyForm(_) => "start-bounty",
+            ComponentId::MessageBountyForm(_) => "message-bounty",
+        }
+    }
+
+    /// The bounty the component acts on, if any.
+    pub fn bounty_id(&self) -> Option<Uuid> {
+        match self {
+            ComponentId::Confirm(bounty)
+            | ComponentId::Accept(bounty)
+            | ComponentId::Decline(bounty)
+            | ComponentId::Complete(bounty)
+            | ComponentId::Rate(bounty)
+            | ComponentId::RatingForm { bounty, .. } => Some(*bounty),
+            ComponentId::Leaderboard { .. }
+            | ComponentId::StartBountyForm(_)
+            | ComponentId::MessageBountyForm(_) => None,
+        }
+    }
+
+    pub fn encode(&self) -> Result<String, ComponentIdError> {
+        let fields = match self {
+            ComponentId::Confirm(bounty)
+            | ComponentId::Accept(bounty)
+            | ComponentId::Decline(bounty)
+            | ComponentId::Complete(bounty)
+            | ComponentId::Rate(bounty) => vec![bounty.to_string()],
+            ComponentId::RatingForm { bounty, score } => {
+                vec![bounty.to_string(), score.to_string()]
+            }
+            ComponentId::Leaderboard {
+                period,
+                metric,
+                currency,
+                page,
+            } => vec![
+                period.as_str().to_string(),
+                metric.as_str().to_string(),
+                currency.clone(),
+                page.to_string(),
+            ],
+            ComponentId::StartBountyForm(user) | ComponentId::MessageBountyForm(user) => {
+                vec![user.to_string()]
+            }
+        };
+
+        if fields.iter().any(|field| field.contains(SEPARATOR)) {
+            return Err(ComponentIdError::Malformed(fields.join(",")));
+        }
+
+        let mut encoded = format!("{}{}{}", VERSION, SEPARATOR, self.kind());
+        for field in fields {
+            encoded.push(SEPARATOR);
+            encoded.push_str(&field);
+        }
+
+        if encoded.len() > MAX_LENGTH {
+            return Err(ComponentIdError::TooLong(encoded.len()));
+        }
+
+        Ok(encoded)
+    }
+
+    pub fn decode(custom_id: &str) -> Result<ComponentId, ComponentIdError> {
+        if custom_id.len() > MAX_LENGTH {
+            return Err(ComponentIdError::TooLong(custom_id.len()));
+        }
+
+        let mut parts = custom_id.split(SEPARATOR);
+        let version = parts.next().unwrap_or_default();
+        if version != VERSION {
+            return decode_legacy(custom_id)
+                .ok_or_else(|| ComponentIdError::UnsupportedVersion(version.to_string()));
+        }
+
+        let kind = parts
+            .next()
+            .ok_or_else(|| ComponentIdError::Malformed(custom_id.to_string()))?;
+        let fields: Vec<&str> = parts.collect();
+        let malformed = || ComponentIdError::Malformed(custom_id.to_string());
+        let uuid = |field: &str| Uuid::parse_str(field).map_err(|_| malformed());
+        let user = |field: &str| field.parse().map(UserId).map_err(|_| malformed());
+
+        let id = match (kind, fields.as_slice()) {
+            ("confirm", [bounty]) => ComponentId::Confirm(uuid(bounty)?),
+            ("accept", [bounty]) => ComponentId::Accept(uuid(bounty)?),
+            ("decline", [bounty]) => ComponentId::Decline(uuid(bounty)?),
+            ("complete", [bounty]) => ComponentId::Complete(uuid(bounty)?),
+            ("rate", [bounty]) => ComponentId::Rate(uuid(bounty)?),
+            ("rating", [bounty, score]) => ComponentId::RatingForm {
+                bounty: uuid(bounty)?,
+                score: score.parse().map_err(|_| malformed())?,
+            },
+            ("leaderboard", [period, metric, currency, page]) => ComponentId::Leaderboard {
+                period: Period::parse(period),
+                metric: Metric::parse(metric),
+                currency: currency.to_string(),
+                page: page.parse().map_err(|_| malformed())?,
+            },
+            ("start-bounty", [hunter]) => ComponentId::StartBountyForm(user(hunter)?),
+            ("message-bounty", [lister]) => ComponentId::MessageBountyForm(user(lister)?),
+            ("confirm" | "accept" | "decline" | "complete" | "rate" | "rating", _)
+            | ("leaderboard" | "start-bounty" | "message-bounty", _) => return Err(malformed()),
+            (kind, _) => return Err(ComponentIdError::UnknownKind(kind.to_string())),
+        };
+
+        Ok(id)
+    }
+}
+
+/// Buttons sent before custom IDs were versioned used `<Prefix>/<bounty id>`.
+fn decode_legacy(custom_id: &str) -> Option<ComponentId> {
+    let (prefix, id) = custom_id.split_once('/')?;
+    let bounty = Uuid::parse_str(id).ok()?;
+
+    match prefix {
+        "bounty" => Some(ComponentId::Confirm(bounty)),
+        "Accept" => Some(ComponentId::Accept(bounty)),
+        "Decline" => Some(ComponentId::Decline(bounty)),
+        "Complete" => Some(ComponentId::Complete(bounty)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNTY: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+    fn bounty() -> Uuid {
+        Uuid::parse_str(BOUNTY).unwrap()
+    }
+
+    #[test]
+    fn every_variant_round_trips() {
+        let ids = [
+            ComponentId::Confirm(bounty()),
+            ComponentId::Accept(bounty()),
+            ComponentId::Decline(bounty()),
+            ComponentId::Complete(bounty()),
+            ComponentId::Rate(bounty()),
+            ComponentId::RatingForm {
+                bounty: bounty(),
+                score: 4,
+            },
+            ComponentId::Leaderboard {
+                period: Period::Week,
+                metric: Metric::Earned,
+                currency: String::from("EUR"),
+                page: 3,
+            },
+            ComponentId::StartBountyForm(UserId(42)),
+            ComponentId::MessageBountyForm(UserId(43)),
+        ];
+
+        for id in ids {
+            let encoded = id.encode().unwrap();
+            assert!(encoded.starts_with("v1:"), "{}", encoded);
+            assert_eq!(ComponentId::decode(&encoded), Ok(id));
+        }
+    }
+
+    #[test]
+    fn ids_over_the_limit_are_rejected() {
+        let id = ComponentId::Leaderboard {
+            period: Period::Week,
+            metric: Metric::Earned,
+            currency: "X".repeat(MAX_LENGTH),
+            page: 0,
+        };
+        let len = format!("v1:leaderboard:week:earned:{}:0", "X".repeat(MAX_LENGTH)).len();
+        assert_eq!(id.encode(), Err(ComponentIdError::TooLong(len)));
+
+        let long = "v1:confirm:".to_string() + &"a".repeat(MAX_LENGTH);
+        assert_eq!(
+            ComponentId::decode(&long),
+            Err(ComponentIdError::TooLong(long.len()))
+        );
+    }
+
+    #[test]
+    fn legacy_ids_decode_to_their_current_form() {
+        for (prefix, expected) in [
+            ("bounty", ComponentId::Confirm(bounty())),
+            ("Accept", ComponentId::Accept(bounty())),
+            ("Decline", ComponentId::Decline(bounty())),
+            ("Complete", ComponentId::Complete(bounty())),
+        ] {
+            let legacy = format!("{}/{}", prefix, BOUNTY);
+            assert_eq!(ComponentId::decode(&legacy), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn malformed_and_unknown_ids_are_rejected() {
+        let malformed = [
+            "v1:confirm",
+            "v1:confirm:not-a-uuid",
+            "v1:rating:67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "v1:rating:67e55044-10b1-426f-9247-bb680e5fe0c8:lots",
+            "v1:start-bounty:someone",
+            "v1",
+        ];
+        for id in malformed {
+            assert_eq!(
+                ComponentId::decode(id),
+                Err(ComponentIdError::Malformed(id.to_string())),
+                "{}",
+                id
+            );
+        }
+
+        assert_eq!(
+            ComponentId::decode(&format!("v1:launch:{}", BOUNTY)),
+            Err(ComponentIdError::UnknownKind(String::from("launch")))
+        );
+        assert_eq!(
+            ComponentId::decode(&format!("v2:confirm:{}", BOUNTY)),
+            Err(ComponentIdError::UnsupportedVersion(String::from("v2")))
+        );
+        assert_eq!(
+            ComponentId::decode(&format!("Rate/{}", BOUNTY)),
+            Err(ComponentIdError::UnsupportedVersion(format!(
+                "Rate/{}",
+                BOUNTY
+            )))
+        );
+        assert_eq!(
+            ComponentId::decode("Accept/not-a-uuid"),
+            Err(ComponentIdError::UnsupportedVersion(String::from(
+                "Accept/not-a-uuid"
+            )))
+        );
+    }
+
+    #[test]
+    fn fields_containing_the_separator_are_rejected() {
+        let id = ComponentId::Leaderboard {
+            period: Period::Week,
+            metric: Metric::Earned,
+            currency: String::from("US:D"),
+            page: 0,
+        };
+        assert!(matches!(id.encode(), Err(ComponentIdError::Malformed(_))));
+    }
+}
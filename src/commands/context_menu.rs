@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use serenity::{
+    builder::{CreateApplicationCommand, CreateInteractionResponse},
+    model::prelude::{
+        command::CommandType,
+        component::{ActionRowComponent, InputTextStyle},
+        interaction::{
+            application_command::{ApplicationCommandInteraction, ResolvedTarget},
+            modal::ModalSubmitInteraction,
+            InteractionResponseType,
+        },
+    },
+};
+
+use crate::commands::{
+    bounty::{self, Bounty, DEFAULT_CURRENCY, MAX_REWARD},
+    component_id::ComponentId,
+    registry::{respond, Registry},
+};
+use crate::discord_util::api::DiscordApi;
+use crate::error::{Error, Result};
+
+const START_WITH: &str = "Start bounty with…";
+const TURN_INTO: &str = "Turn into bounty";
+
+const MAX_DESCRIPTION: usize = 4000;
+
+pub fn register_routes(registry: &mut Registry) {
+    registry.command(START_WITH, register_user, |api, command| {
+        Box::pin(async move { respond(api, command, run(command)?).await })
+    });
+    registry.command(TURN_INTO, register_message, |api, command| {
+        Box::pin(async move { respond(api, command, run(command)?).await })
+    });
+    registry.modal("start-bounty", |api, modal, id| {
+        Box::pin(submit(api, modal, id))
+    });
+    registry.modal("message-bounty", |api, modal, id| {
+        Box::pin(submit(api, modal, id))
+    });
+}
+
+pub fn register_user(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command.name(START_WITH).kind(CommandType::User)
+}
+
+pub fn register_message(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command.name(TURN_INTO).kind(CommandType::Message)
+}
+
+/// Asks for the bounty details of a user or message context-menu command.
+pub fn run<'a>(command: &ApplicationCommandInteraction) -> Result<CreateInteractionResponse<'a>> {
+    let (custom_id, description) = match command.data.target() {
+        Some(ResolvedTarget::User(hunter, _)) => {
+            if hunter.id == command.user.id {
+                return Err(Error::Validation(String::from(
+                    "You cannot start a bounty with yourself",
+                )));
+            }
+            (ComponentId::StartBountyForm(hunter.id), None)
+        }
+        Some(ResolvedTarget::Message(message)) => {
+            if message.author.id == command.user.id {
+                return Err(Error::Validation(String::from(
+                    "You cannot hunt your own bounty",
+                )));
+            }
+            let description: String = message.content.chars().take(MAX_DESCRIPTION).collect();
+            (
+                ComponentId::MessageBountyForm(message.author.id),
+                Some(description),
+            )
+        }
+        _ => {
+            return Err(Error::Validation(String::from(
+                "Could not resolve the target of this command",
+            )))
+        }
+    };
+    let custom_id = custom_id.encode()?;
+
+    Ok(CreateInteractionResponse::default()
+        .kind(InteractionResponseType::Modal)
+        .interaction_response_data(|d| {
+            d.custom_id(custom_id)
+                .title("Start a bounty")
+                .components(|c| {
+                    c.create_action_row(|r| {
+                        r.create_input_text(|t| {
+                            t.custom_id("bounty-number")
+                                .label("Bounty number")
+                                .style(InputTextStyle::Short)
+                                .required(true)
+                        })
+                    })
+                    .create_action_row(|r| {
+                        r.create_input_text(|t| {
+                            t.custom_id("reward")
+                                .label("Reward (optional)")
+                                .style(InputTextStyle::Short)
+                                .required(false)
+                        })
+                    })
+                    .create_action_row(|r| {
+                        r.create_input_text(|t| {
+                            t.custom_id("currency")
+                                .label("Currency (defaults to USD)")
+                                .style(InputTextStyle::Short)
+                                .required(false)
+                        })
+                    })
+                    .create_action_row(|r| {
+                        r.create_input_text(|t| {
+                            t.custom_id("description")
+                                .label("Description")
+                                .style(InputTextStyle::Paragraph)
+                                .max_length(MAX_DESCRIPTION as u64)
+                                .required(false);
+                            if let Some(description) = &description {
+                                t.value(description);
+                            }
+                            t
+                        })
+                    })
+                })
+        })
+        .clone())
+}
+
+/// Starts the bounty described in the modal, `form` carrying the other participant.
+pub async fn submit(
+    api: &dyn DiscordApi,
+    modal: &ModalSubmitInteraction,
+    form: ComponentId,
+) -> Result<()> {
+    let (bounty, message, ephemeral) = build_bounty(api, modal, form).await?;
+    bounty::start(
+        api,
+        (modal.id, &modal.token),
+        bounty,
+        modal.user.id,
+        &message,
+        ephemeral,
+    )
+    .await
+}
+
+async fn build_bounty(
+    api: &dyn DiscordApi,
+    modal: &ModalSubmitInteraction,
+    form: ComponentId,
+) -> Result<(Bounty, String, bool)> {
+    let values = input_values(modal);
+    let number: u32 = values
+        .get("bounty-number")
+        .and_then(|number| number.trim().trim_start_matches('#').parse().ok())
+        .filter(|number| *number >= 1)
+        .ok_or_else(|| Error::Validation(String::from("Invalid bounty number")))?;
+    let reward: u64 = match values.get("reward").map(|reward| reward.trim()) {
+        Some(reward) if !reward.is_empty() => reward
+            .parse()
+            .ok()
+            .filter(|reward| *reward <= MAX_REWARD)
+            .ok_or_else(|| {
+                Error::Validation(format!(
+                    "Rewards must be whole numbers up to {}",
+                    MAX_REWARD
+                ))
+            })?,
+        _ => 0,
+    };
+    let currency = match values.get("currency").map(|currency| currency.trim()) {
+        Some(currency) if !currency.is_empty() => bounty::parse_currency(currency)?,
+        _ => String::from(DEFAULT_CURRENCY),
+    };
+    let description = values
+        .get("description")
+        .map(|description| description.trim().to_string())
+        .filter(|description| !description.is_empty());
+
+    let other_id = match form {
+        ComponentId::StartBountyForm(user) | ComponentId::MessageBountyForm(user) => user,
+        _ => return Err(Error::Validation(String::from("Unknown bounty form"))),
+    };
+    let other = api.user(other_id).await?;
+
+    let (mut bounty, message, ephemeral) = match form {
+        ComponentId::StartBountyForm(_) => (
+            Bounty::new(modal.user.clone(), other, number, reward, currency),
+            String::from("Please confirm the bounty"),
+            true,
+        ),
+        _ => {
+            let message = format!(
+                "<@{}>, {} would like to hunt this bounty. Please confirm the bounty.",
+                other.id, modal.user.name
+            );
+            (
+                Bounty::new(other, modal.user.clone(), number, reward, currency),
+                message,
+                false,
+            )
+        }
+    };
+    bounty.description = description;
+
+    Ok((bounty, message, ephemeral))
+}
+
+fn input_values(modal: &ModalSubmitInteraction) -> HashMap<String, String> {
+    modal
+        .data
+        .components
+        .iter()
+        .flat_map(|row| row.components.iter())
+        .filter_map(|component| match component {
+            ActionRowComponent::InputText(input) => {
+                Some((input.custom_id.clone(), input.value.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,483 @@
+use std::collections::HashMap;
+
+use serenity::{
+    builder::{
+        CreateApplicationCommandOption, CreateComponents, CreateEmbed, CreateInteractionResponse,
+    },
+    model::{
+        id::UserId,
+        prelude::{
+            command::CommandOptionType,
+            component::ButtonStyle,
+            interaction::{
+                application_command::{ApplicationCommandInteraction, CommandDataOptionValue},
+                message_component::MessageComponentInteraction,
+                InteractionResponseType,
+            },
+        },
+        user::User,
+        Timestamp,
+    },
+};
+
+use tracing::warn;
+
+use crate::commands::{
+    bounty::{BountyStatus, DEFAULT_CURRENCY},
+    component_id::ComponentId,
+    registry::{mismatched, respond, Registry},
+};
+use crate::discord_util::api::DiscordApi;
+use crate::error::Result;
+use crate::storage::{
+    ledger::EntryKind,
+    seasons,
+    store::{self, Store},
+};
+
+const PAGE_SIZE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Week,
+    Month,
+    Season,
+    AllTime,
+}
+
+impl Period {
+    pub fn parse(value: &str) -> Period {
+        match value {
+            "week" => Period::Week,
+            "month" => Period::Month,
+            "season" => Period::Season,
+            _ => Period::AllTime,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Period::Week => "week",
+            Period::Month => "month",
+            Period::Season => "season",
+            Period::AllTime => "all-time",
+        }
+    }
+
+    /// Unix timestamps bounding the activity that counts towards this period, end exclusive.
+    fn window(&self) -> Result<(i64, i64)> {
+        let now = Timestamp::now().unix_timestamp();
+        Ok(match self {
+            Period::Week => (now - 7 * 24 * 60 * 60, i64::MAX),
+            Period::Month => (now - 30 * 24 * 60 * 60, i64::MAX),
+            Period::Season => seasons::current()?.map_or((0, 0), |season| season.window()),
+            Period::AllTime => (i64::MIN, i64::MAX),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Completed,
+    Earned,
+    Rating,
+}
+
+impl Metric {
+    pub fn parse(value: &str) -> Metric {
+        match value {
+            "earned" => Metric::Earned,
+            "rating" => Metric::Rating,
+            _ => Metric::Completed,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Metric::Completed => "completed",
+            Metric::Earned => "earned",
+            Metric::Rating => "rating",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Metric::Completed => "Bounties completed",
+            Metric::Earned => "Reward earned",
+            Metric::Rating => "Average rating",
+        }
+    }
+}
+
+pub struct Standing {
+    pub user: User,
+    pub value: f64,
+    pub label: String,
+}
+
+pub fn register_routes(registry: &mut Registry) {
+    registry.subcommand("bounty", "leaderboard", register, |api, command| {
+        Box::pin(async move { respond(api, command, run(command)?).await })
+    });
+    registry.component("leaderboard", |api, component, id| match id {
+        ComponentId::Leaderboard {
+            period,
+            metric,
+            currency,
+            page,
+        } => {
+            Box::pin(async move { paginate(api, component, period, metric, &currency, page).await })
+        }
+        other => mismatched(other),
+    });
+}
+
+pub fn register(
+    option: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    option
+        .name("leaderboard")
+        .description("Show the most active bounty hunters")
+        .kind(CommandOptionType::SubCommand)
+        .create_sub_option(|option| {
+            option
+                .name("period")
+                .description("The period to rank (defaults to all-time)")
+                .kind(CommandOptionType::String)
+                .add_string_choice("Week", "week")
+                .add_string_choice("Month", "month")
+                .add_string_choice("Current season", "season")
+                .add_string_choice("All-time", "all-time")
+        })
+        .create_sub_option(|option| {
+            option
+                .name("metric")
+                .description("What to rank hunters by (defaults to bounties completed)")
+                .kind(CommandOptionType::String)
+                .add_string_choice("Bounties completed", "completed")
+                .add_string_choice("Reward earned", "earned")
+                .add_string_choice("Average rating", "rating")
+        })
+        .create_sub_option(|option| {
+            option
+                .name("currency")
+                .description("The currency to rank rewards in (defaults to USD)")
+                .kind(CommandOptionType::String)
+        })
+}
+
+pub fn run<'a>(command: &ApplicationCommandInteraction) -> Result<CreateInteractionResponse<'a>> {
+    let mut period = Period::AllTime;
+    let mut metric = Metric::Completed;
+    let mut currency = String::from(DEFAULT_CURRENCY);
+    for arg in &command.data.options[0].options {
+        if let Some(CommandDataOptionValue::String(value)) = &arg.resolved {
+            match arg.name.as_str() {
+                "period" => period = Period::parse(value),
+                "metric" => metric = Metric::parse(value),
+                "currency" => {
+                    currency = value
+                        .chars()
+                        .filter(|c| c.is_ascii_alphanumeric())
+                        .take(10)
+                        .collect::<String>()
+                        .to_uppercase()
+                }
+                _ => warn!(option = %arg.name, "Unknown argument"),
+            }
+        }
+    }
+
+    let (embed, components) = render(period, metric, &currency, 0)?;
+
+    Ok(CreateInteractionResponse::default()
+        .kind(InteractionResponseType::ChannelMessageWithSource)
+        .interaction_response_data(|d| d.set_embed(embed).set_components(components))
+        .clone())
+}
+
+/// Handles the previous/next buttons.
+pub async fn paginate(
+    api: &dyn DiscordApi,
+    component: &MessageComponentInteraction,
+    period: Period,
+    metric: Metric,
+    currency: &str,
+    page: usize,
+) -> Result<()> {
+    let (embed, components) = render(period, metric, currency, page)?;
+
+    let mut response = CreateInteractionResponse::default();
+    response
+        .kind(InteractionResponseType::UpdateMessage)
+        .interaction_response_data(|d| d.set_embed(embed).set_components(components));
+    api.respond(component.id, &component.token, response).await
+}
+
+/// Ranks hunters by `metric` over `period`, best first.
+pub fn standings(
+    store: &Store,
+    (since, until): (i64, i64),
+    metric: Metric,
+    currency: &str,
+) -> Vec<Standing> {
+    let within = |timestamp: i64| since <= timestamp && timestamp < until;
+    let users: HashMap<UserId, User> = store
+        .bounties
+        .values()
+        .map(|bounty| (bounty.hunter.id, bounty.hunter.clone()))
+        .collect();
+
+    let mut totals: HashMap<UserId, (f64, usize)> = HashMap::new();
+    match metric {
+        Metric::Completed => {
+            for bounty in store.bounties.values() {
+                let completed_at = match bounty.completed_at {
+                    Some(completed_at) => completed_at.unix_timestamp(),
+                    None => continue,
+                };
+                if bounty.status == BountyStatus::Completed && within(completed_at) {
+                    totals.entry(bounty.hunter.id).or_insert((0.0, 0)).0 += 1.0;
+                }
+            }
+        }
+        Metric::Earned => {
+            for entry in store.ledger.entries() {
+                if entry.kind == EntryKind::Release
+                    && entry.currency == currency
+                    && within(entry.timestamp.unix_timestamp())
+                {
+                    totals.entry(entry.user).or_insert((0.0, 0)).0 += entry.amount as f64;
+                }
+            }
+        }
+        Metric::Rating => {
+            for rating in &store.ratings {
+                if within(rating.timestamp.unix_timestamp()) {
+                    let total = totals.entry(rating.hunter).or_insert((0.0, 0));
+                    total.0 += rating.score as f64;
+                    total.1 += 1;
+                }
+            }
+        }
+    }
+
+    let mut standings: Vec<Standing> = totals
+        .into_iter()
+        .filter_map(|(user_id, (total, count))| {
+            let user = users.get(&user_id)?.clone();
+            let (value, label) = match metric {
+                Metric::Completed => (total, format!("{} completed", total)),
+                Metric::Earned => (total, format!("{} {}", total, currency)),
+                Metric::Rating => {
+                    let average = total / count as f64;
+                    (average, format!("{:.1}/5 ({} ratings)", average, count))
+                }
+            };
+            Some(Standing { user, value, label })
+        })
+        .collect();
+
+    standings.sort_by(|a, b| {
+        b.value
+            .partial_cmp(&a.value)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.user.name.cmp(&b.user.name))
+    });
+    standings
+}
+
+/// The page to show, at most the last one, and how many pages `standings` fill.
+fn clamp_page(standings: usize, page: usize) -> (usize, usize) {
+    let pages = standings.div_ceil(PAGE_SIZE).max(1);
+    (page.min(pages - 1), pages)
+}
+
+fn render(
+    period: Period,
+    metric: Metric,
+    currency: &str,
+    page: usize,
+) -> Result<(CreateEmbed, CreateComponents)> {
+    let window = period.window()?;
+    let standings = store::read(|store| standings(store, window, metric, currency));
+    let (page, pages) = clamp_page(standings.len(), page);
+
+    let mut description = String::new();
+    for (rank, standing) in standings
+        .iter()
+        .enumerate()
+        .skip(page * PAGE_SIZE)
+        .take(PAGE_SIZE)
+    {
+        description.push_str(&format!(
+            "**{}.** {} — {}\n",
+            rank + 1,
+            standing.user.name,
+            standing.label
+        ));
+    }
+    if description.is_empty() {
+        description.push_str("No bounties recorded for this period yet.");
+    }
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(format!(
+            "Leaderboard: {} ({})",
+            metric.title(),
+            period.as_str()
+        ))
+        .description(description)
+        .footer(|f| f.text(format!("Page {}/{}", page + 1, pages)));
+
+    let custom_id = |page: usize| {
+        ComponentId::Leaderboard {
+            period,
+            metric,
+            currency: currency.to_string(),
+            page,
+        }
+        .encode()
+    };
+
+    let previous = custom_id(page.saturating_sub(1))?;
+    let next = custom_id(page + 1)?;
+
+    let mut components = CreateComponents::default();
+    components.create_action_row(|r| {
+        r.create_button(|b| {
+            b.style(ButtonStyle::Secondary)
+                .label("Previous")
+                .custom_id(previous)
+                .disabled(page == 0)
+        })
+        .create_button(|b| {
+            b.style(ButtonStyle::Secondary)
+                .label("Next")
+                .custom_id(next)
+                .disabled(page + 1 >= pages)
+        })
+    });
+
+    Ok((embed, components))
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::model::id::UserId;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::commands::bounty::Bounty;
+    use crate::storage::ratings::Rating;
+
+    fn user(id: u64, name: &str) -> User {
+        let mut user = User::default();
+        user.id = UserId(id);
+        user.name = name.to_string();
+        user
+    }
+
+    fn at(seconds: i64) -> Timestamp {
+        Timestamp::from_unix_timestamp(seconds).unwrap()
+    }
+
+    fn completed(store: &mut Store, hunter: &User, completed_at: i64) -> Uuid {
+        let id = Uuid::new_v4();
+        let mut bounty = Bounty::new(user(1, "lister"), hunter.clone(), 1, 0, String::from("USD"));
+        bounty.status = BountyStatus::Completed;
+        bounty.completed_at = Some(at(completed_at));
+        store.bounties.insert(id, bounty);
+        id
+    }
+
+    fn ranking(standings: &[Standing]) -> Vec<(&str, &str)> {
+        standings
+            .iter()
+            .map(|standing| (standing.user.name.as_str(), standing.label.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn only_activity_within_the_window_counts() {
+        let (alice, bob) = (user(2, "alice"), user(3, "bob"));
+        let mut store = Store::default();
+        completed(&mut store, &alice, 999);
+        completed(&mut store, &alice, 1000);
+        completed(&mut store, &bob, 1999);
+        completed(&mut store, &bob, 2000);
+        let mut open = Bounty::new(user(1, "lister"), bob.clone(), 2, 0, String::from("USD"));
+        open.completed_at = Some(at(1500));
+        store.bounties.insert(Uuid::new_v4(), open);
+
+        let standings = standings(&store, (1000, 2000), Metric::Completed, "USD");
+        assert_eq!(
+            ranking(&standings),
+            vec![("alice", "1 completed"), ("bob", "1 completed")]
+        );
+    }
+
+    #[test]
+    fn rewards_earned_are_ranked_per_currency() {
+        let (alice, bob) = (user(2, "alice"), user(3, "bob"));
+        let mut store = Store::default();
+        for (hunter, reward, currency) in
+            [(&alice, 10, "USD"), (&bob, 30, "EUR"), (&alice, 5, "EUR")]
+        {
+            let id = completed(&mut store, hunter, 1000);
+            store.ledger.hold(id, UserId(1), reward, currency);
+            store.ledger.release(id, hunter.id);
+        }
+        let held = completed(&mut store, &alice, 1000);
+        store.ledger.hold(held, UserId(1), 100, "EUR");
+
+        let window = (i64::MIN, i64::MAX);
+        assert_eq!(
+            ranking(&standings(&store, window, Metric::Earned, "EUR")),
+            vec![("bob", "30 EUR"), ("alice", "5 EUR")]
+        );
+        assert_eq!(
+            ranking(&standings(&store, window, Metric::Earned, "USD")),
+            vec![("alice", "10 USD")]
+        );
+    }
+
+    #[test]
+    fn ties_are_ordered_by_name() {
+        let (alice, bob, carol) = (user(2, "alice"), user(3, "bob"), user(4, "carol"));
+        let mut store = Store::default();
+        for (hunter, scores) in [(&carol, [4, 4]), (&alice, [5, 3]), (&bob, [5, 5])] {
+            for score in scores {
+                let bounty_id = completed(&mut store, hunter, 1000);
+                store.ratings.push(Rating {
+                    bounty_id,
+                    hunter: hunter.id,
+                    rater: UserId(1),
+                    score,
+                    comment: None,
+                    timestamp: at(1000),
+                });
+            }
+        }
+
+        let window = (i64::MIN, i64::MAX);
+        assert_eq!(
+            ranking(&standings(&store, window, Metric::Rating, "USD")),
+            vec![
+                ("bob", "5.0/5 (2 ratings)"),
+                ("alice", "4.0/5 (2 ratings)"),
+                ("carol", "4.0/5 (2 ratings)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn pages_past_the_end_show_the_last_page() {
+        assert_eq!(clamp_page(0, 0), (0, 1));
+        assert_eq!(clamp_page(0, 3), (0, 1));
+        assert_eq!(clamp_page(PAGE_SIZE, 1), (0, 1));
+        assert_eq!(clamp_page(PAGE_SIZE + 1, 1), (1, 2));
+        assert_eq!(clamp_page(PAGE_SIZE + 1, 9), (1, 2));
+    }
+}
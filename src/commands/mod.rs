@@ -1 +1,14 @@
+pub mod achievements;
+pub mod admin;
+pub mod audit;
+pub mod autocomplete;
+pub mod balance;
 pub mod bounty;
+pub mod component_id;
+pub mod context_menu;
+pub mod leaderboard;
+pub mod profile;
+pub mod rating;
+pub mod registry;
+pub mod seasons;
+pub mod staff;
@@ -0,0 +1,241 @@
+use serenity::{
+    builder::{CreateApplicationCommand, CreateInteractionResponse},
+    model::{
+        id::UserId,
+        prelude::{
+            command::CommandOptionType,
+            interaction::{
+                application_command::{ApplicationCommandInteraction, CommandDataOptionValue},
+                InteractionResponseType,
+            },
+        },
+        user::User,
+    },
+};
+
+use crate::commands::bounty::BountyStatus;
+use crate::commands::registry::{respond, Registry};
+use crate::storage::{
+    ratings,
+    store::{self, Store},
+};
+
+pub fn register_routes(registry: &mut Registry) {
+    registry.command("profile", register, |api, command| {
+        Box::pin(respond(api, command, run(command)))
+    });
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("profile")
+        .description("Show the bounty history of a user")
+        .create_option(|option| {
+            option
+                .name("user")
+                .description("The user to look up (defaults to you)")
+                .kind(CommandOptionType::User)
+        })
+}
+
+pub fn run<'a>(command: &ApplicationCommandInteraction) -> CreateInteractionResponse<'a> {
+    let mut user: User = command.user.clone();
+    for arg in &command.data.options {
+        if let Some(CommandDataOptionValue::User(target, _)) = &arg.resolved {
+            user = target.clone();
+        }
+    }
+
+    let profile = store::read(|store| profile(store, user.id));
+
+    CreateInteractionResponse::default()
+        .kind(InteractionResponseType::ChannelMessageWithSource)
+        .interaction_response_data(|d| {
+            d.embed(|e| {
+                e.title(format!("Profile of {}", user.name))
+                    .thumbnail(user.face())
+                    .fields(profile.into_iter().map(|(name, value)| (name, value, true)))
+            })
+        })
+        .clone()
+}
+
+/// The fields shown on the profile of `user`.
+fn profile(store: &Store, user: UserId) -> Vec<(&'static str, String)> {
+    let listed = store
+        .bounties
+        .values()
+        .filter(|b| b.lister.id == user)
+        .count();
+    let hunted: Vec<_> = store
+        .bounties
+        .values()
+        .filter(|b| {
+            b.hunter.id == user
+                && !matches!(b.status, BountyStatus::Pending | BountyStatus::Confirming)
+        })
+        .collect();
+    let completed = hunted
+        .iter()
+        .filter(|b| b.status == BountyStatus::Completed)
+        .count();
+
+    let time_to_accept =
+        average(hunted.iter().filter_map(|b| {
+            Some(b.accepted_at?.unix_timestamp() - b.created_at?.unix_timestamp())
+        }));
+    let time_to_complete =
+        average(hunted.iter().filter_map(|b| {
+            Some(b.completed_at?.unix_timestamp() - b.accepted_at?.unix_timestamp())
+        }));
+
+    let active: Vec<String> = store
+        .bounties
+        .values()
+        .filter(|b| b.lister.id == user || b.hunter.id == user)
+        .filter(|b| matches!(b.status, BountyStatus::Confirmed | BountyStatus::Accepted))
+        .filter_map(|b| b.channel_id)
+        .map(|channel| format!("<#{}>", channel))
+        .collect();
+
+    let completion_rate = if hunted.is_empty() {
+        String::from("-")
+    } else {
+        format!("{:.0}%", completed as f64 * 100.0 / hunted.len() as f64)
+    };
+
+    vec![
+        ("Bounties listed", listed.to_string()),
+        ("Bounties hunted", hunted.len().to_string()),
+        ("Completion rate", completion_rate),
+        ("Average time to accept", format_duration(time_to_accept)),
+        (
+            "Average time to complete",
+            format_duration(time_to_complete),
+        ),
+        (
+            "Rating",
+            ratings::reputation(&store.ratings, user)
+                .map(|reputation| reputation.to_string())
+                .unwrap_or_else(|| String::from("Not rated yet")),
+        ),
+        (
+            "Active bounties",
+            if active.is_empty() {
+                String::from("None")
+            } else {
+                active.join(", ")
+            },
+        ),
+    ]
+}
+
+fn average(seconds: impl Iterator<Item = i64>) -> Option<i64> {
+    let seconds: Vec<i64> = seconds.collect();
+    if seconds.is_empty() {
+        None
+    } else {
+        Some(seconds.iter().sum::<i64>() / seconds.len() as i64)
+    }
+}
+
+fn format_duration(seconds: Option<i64>) -> String {
+    let seconds = match seconds {
+        Some(seconds) => seconds.max(0),
+        None => return String::from("-"),
+    };
+
+    let (days, hours, minutes) = (seconds / 86400, seconds % 86400 / 3600, seconds % 3600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::model::{id::ChannelId, Timestamp};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::commands::bounty::Bounty;
+    use crate::storage::ratings::Rating;
+
+    const LISTER: UserId = UserId(1);
+    const HUNTER: UserId = UserId(2);
+
+    fn bounty(store: &mut Store, status: BountyStatus, times: [i64; 3]) -> Uuid {
+        let user = |id: UserId| {
+            let mut user = User::default();
+            user.id = id;
+            user
+        };
+        let at = |seconds: i64| Some(Timestamp::from_unix_timestamp(seconds).unwrap());
+        let mut bounty = Bounty::new(user(LISTER), user(HUNTER), 1, 0, String::from("USD"));
+        bounty.status = status;
+        let [created, accepted, completed] = times;
+        bounty.created_at = at(created);
+        bounty.accepted_at = at(accepted).filter(|_| accepted > 0);
+        bounty.completed_at = at(completed).filter(|_| completed > 0);
+        let id = Uuid::new_v4();
+        store.bounties.insert(id, bounty);
+        id
+    }
+
+    fn field(profile: &[(&str, String)], name: &str) -> String {
+        profile
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| value.clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn the_hunter_profile_sums_up_hunted_bounties() {
+        let mut store = Store::default();
+        let completed = bounty(
+            &mut store,
+            BountyStatus::Completed,
+            [0, 3600, 3 * 86400 + 3600],
+        );
+        bounty(&mut store, BountyStatus::Completed, [0, 60, 120]);
+        bounty(&mut store, BountyStatus::Declined, [0, 0, 0]);
+        bounty(&mut store, BountyStatus::Pending, [0, 0, 0]);
+        let active = bounty(&mut store, BountyStatus::Accepted, [0, 7200, 0]);
+        store.bounties.get_mut(&active).unwrap().channel_id = Some(ChannelId(55));
+        store.ratings.push(Rating {
+            bounty_id: completed,
+            hunter: HUNTER,
+            rater: LISTER,
+            score: 4,
+            comment: None,
+            timestamp: Timestamp::now(),
+        });
+
+        let profile = profile(&store, HUNTER);
+        assert_eq!(field(&profile, "Bounties listed"), "0");
+        assert_eq!(field(&profile, "Bounties hunted"), "4");
+        assert_eq!(field(&profile, "Completion rate"), "50%");
+        assert_eq!(field(&profile, "Average time to accept"), "1h 0m");
+        assert_eq!(field(&profile, "Average time to complete"), "1d 12h");
+        assert_eq!(field(&profile, "Rating"), "4.0/5 (1 rating)");
+        assert_eq!(field(&profile, "Active bounties"), "<#55>");
+    }
+
+    #[test]
+    fn a_new_user_has_an_empty_profile() {
+        let mut store = Store::default();
+        bounty(&mut store, BountyStatus::Pending, [0, 0, 0]);
+
+        let profile = profile(&store, LISTER);
+        assert_eq!(field(&profile, "Bounties listed"), "1");
+        assert_eq!(field(&profile, "Bounties hunted"), "0");
+        assert_eq!(field(&profile, "Completion rate"), "-");
+        assert_eq!(field(&profile, "Average time to accept"), "-");
+        assert_eq!(field(&profile, "Rating"), "Not rated yet");
+        assert_eq!(field(&profile, "Active bounties"), "None");
+    }
+}
@@ -0,0 +1,183 @@
+use serenity::{
+    builder::{CreateInteractionResponse, CreateMessage},
+    model::{
+        prelude::{
+            component::{ActionRowComponent, InputTextStyle},
+            interaction::{
+                message_component::MessageComponentInteraction, modal::ModalSubmitInteraction,
+                InteractionResponseType,
+            },
+        },
+        Timestamp,
+    },
+};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::commands::{
+    achievements,
+    bounty::{configured_guild, ephemeral_response, Bounty, BountyStatus},
+    component_id::ComponentId,
+    registry::{mismatched, Registry},
+};
+use crate::discord_util::api::DiscordApi;
+use crate::error::{Error, Result};
+use crate::storage::{
+    ratings::{self, Rating},
+    store,
+};
+
+const STARS: [&str; 5] = ["★", "★★", "★★★", "★★★★", "★★★★★"];
+
+pub fn register_routes(registry: &mut Registry) {
+    registry.component("rate", |api, component, id| match id {
+        ComponentId::Rate(bounty_id) => Box::pin(select(api, component, bounty_id)),
+        other => mismatched(other),
+    });
+    registry.modal("rating", |api, modal, id| match id {
+        ComponentId::RatingForm { bounty, score } => Box::pin(submit(api, modal, bounty, score)),
+        other => mismatched(other),
+    });
+}
+
+/// Asks the lister of a completed bounty to rate the hunter.
+pub async fn prompt(api: &dyn DiscordApi, id: &Uuid, bounty: &Bounty) {
+    let custom_id = match ComponentId::Rate(*id).encode() {
+        Ok(custom_id) => custom_id,
+        Err(err) => {
+            error!(error = %err, "Could not build rating menu");
+            return;
+        }
+    };
+    let message = format!(
+        "Bounty {} with {} is complete. How did they do?",
+        bounty.bounty_number, bounty.hunter.name
+    );
+
+    let mut dm = CreateMessage::default();
+    dm.content(message).components(|c| {
+        c.create_action_row(|r| {
+            r.create_select_menu(|menu| {
+                menu.custom_id(&custom_id)
+                    .placeholder("Rate the bounty hunter")
+                    .options(|o| {
+                        for (score, stars) in STARS.iter().enumerate() {
+                            o.create_option(|opt| opt.label(stars).value(score + 1));
+                        }
+                        o
+                    })
+            })
+        })
+    });
+    if let Err(err) = api.direct_message(bounty.lister.id, dm).await {
+        warn!(error = %err, "Could not ask for a rating");
+    }
+}
+
+/// Opens the comment modal once the lister picked a score.
+pub async fn select(
+    api: &dyn DiscordApi,
+    component: &MessageComponentInteraction,
+    bounty_id: Uuid,
+) -> Result<()> {
+    let score = component
+        .data
+        .values
+        .first()
+        .and_then(|score| score.parse().ok())
+        .ok_or_else(|| Error::Validation(String::from("Please pick a rating")))?;
+    let custom_id = ComponentId::RatingForm {
+        bounty: bounty_id,
+        score,
+    }
+    .encode()?;
+
+    let mut response = CreateInteractionResponse::default();
+    response
+        .kind(InteractionResponseType::Modal)
+        .interaction_response_data(|d| {
+            d.custom_id(&custom_id)
+                .title("Rate the bounty hunter")
+                .components(|c| {
+                    c.create_action_row(|r| {
+                        r.create_input_text(|t| {
+                            t.custom_id("comment")
+                                .label("Comment (optional)")
+                                .style(InputTextStyle::Paragraph)
+                                .max_length(500)
+                                .required(false)
+                        })
+                    })
+                })
+        });
+    api.respond(component.id, &component.token, response).await
+}
+
+pub async fn submit(
+    api: &dyn DiscordApi,
+    modal: &ModalSubmitInteraction,
+    bounty_id: Uuid,
+    score: u8,
+) -> Result<()> {
+    let comment = modal
+        .data
+        .components
+        .iter()
+        .flat_map(|row| row.components.iter())
+        .find_map(|component| match component {
+            ActionRowComponent::InputText(input) if input.custom_id == "comment" => {
+                Some(input.value.trim().to_string())
+            }
+            _ => None,
+        })
+        .filter(|comment| !comment.is_empty());
+
+    let bounty = store::write(|store| {
+        let bounty = store
+            .bounties
+            .get(&bounty_id)
+            .ok_or_else(|| Error::Validation(String::from("Bounty not found")))?;
+        if bounty.lister.id != modal.user.id {
+            return Err(Error::Permission(String::from(
+                "Only the bounty lister can rate the hunter",
+            )));
+        }
+        if bounty.status != BountyStatus::Completed {
+            return Err(Error::Validation(String::from(
+                "Only completed bounties can be rated",
+            )));
+        }
+        let bounty = bounty.clone();
+        ratings::rate(
+            &mut store.ratings,
+            Rating {
+                bounty_id,
+                hunter: bounty.hunter.id,
+                rater: modal.user.id,
+                score,
+                comment,
+                timestamp: Timestamp::now(),
+            },
+        )?;
+        Ok(bounty)
+    })??;
+
+    let message = format!(
+        "Thanks! You rated the hunter {}.",
+        STARS[score as usize - 1]
+    );
+    api.respond(modal.id, &modal.token, ephemeral_response(&message))
+        .await?;
+
+    // The rating may complete a five-star streak. Ratings arrive by DM, so the guild for
+    // achievement roles comes from the config.
+    let awarded = async {
+        let guild_id = modal.guild_id.map_or_else(configured_guild, Ok)?;
+        achievements::award(api, guild_id, bounty_id, &bounty).await
+    }
+    .await;
+    if let Err(err) = awarded {
+        warn!(error = %err, "Could not award achievements");
+    }
+    Ok(())
+}
@@ -0,0 +1,362 @@
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, future::Future, pin::Pin, time::Instant};
+use tracing::{error, info, warn, Instrument};
+
+use serenity::{
+    builder::{
+        CreateApplicationCommand, CreateApplicationCommandOption, CreateApplicationCommands,
+        CreateInteractionResponse, CreateInteractionResponseFollowup, EditInteractionResponse,
+    },
+    model::{
+        id::InteractionId,
+        prelude::interaction::{
+            application_command::ApplicationCommandInteraction,
+            autocomplete::AutocompleteInteraction, message_component::MessageComponentInteraction,
+            modal::ModalSubmitInteraction, Interaction, InteractionResponseType, MessageFlags,
+        },
+    },
+};
+
+use crate::commands::{
+    admin, audit, autocomplete, balance,
+    bounty::{self, ephemeral_response},
+    component_id::ComponentId,
+    context_menu, leaderboard, profile, rating, seasons,
+};
+use crate::discord_util::api::DiscordApi;
+use crate::error::{Error, Result};
+use crate::logging;
+use crate::metrics;
+
+static REGISTRY: Lazy<Registry> = Lazy::new(|| {
+    let mut registry = Registry::default();
+    bounty::register_routes(&mut registry);
+    leaderboard::register_routes(&mut registry);
+    seasons::register_routes(&mut registry);
+    admin::register_routes(&mut registry);
+    audit::register_routes(&mut registry);
+    autocomplete::register_routes(&mut registry);
+    rating::register_routes(&mut registry);
+    balance::register_routes(&mut registry);
+    profile::register_routes(&mut registry);
+    context_menu::register_routes(&mut registry);
+    registry
+});
+
+/// A running handler. Errors are reported to the user by `dispatch`.
+pub type BoxFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+pub type CommandHandler =
+    for<'a> fn(&'a dyn DiscordApi, &'a ApplicationCommandInteraction) -> BoxFuture<'a>;
+/// Handles a component, receiving its decoded custom ID.
+pub type ComponentHandler =
+    for<'a> fn(&'a dyn DiscordApi, &'a MessageComponentInteraction, ComponentId) -> BoxFuture<'a>;
+/// Handles a modal, receiving its decoded custom ID.
+pub type ModalHandler =
+    for<'a> fn(&'a dyn DiscordApi, &'a ModalSubmitInteraction, ComponentId) -> BoxFuture<'a>;
+pub type AutocompleteHandler =
+    for<'a> fn(&'a dyn DiscordApi, &'a AutocompleteInteraction) -> BoxFuture<'a>;
+
+pub type CommandDefinition = fn(&mut CreateApplicationCommand) -> &mut CreateApplicationCommand;
+pub type SubcommandDefinition =
+    fn(&mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption;
+
+enum Definition {
+    Command(CommandDefinition, CommandHandler),
+    Group(&'static str, Vec<Subcommand>),
+}
+
+struct Subcommand {
+    name: &'static str,
+    definition: SubcommandDefinition,
+    handler: CommandHandler,
+}
+
+/// Every slash command, component and modal the bot knows, keyed by name or custom ID kind.
+#[derive(Default)]
+pub struct Registry {
+    commands: Vec<(&'static str, Definition)>,
+    components: HashMap<&'static str, ComponentHandler>,
+    modals: HashMap<&'static str, ModalHandler>,
+    autocompletes: HashMap<&'static str, AutocompleteHandler>,
+}
+
+impl Registry {
+    pub fn command(
+        &mut self,
+        name: &'static str,
+        definition: CommandDefinition,
+        handler: CommandHandler,
+    ) {
+        self.commands
+            .push((name, Definition::Command(definition, handler)));
+    }
+
+    /// Declares a command whose options are subcommands registered by `subcommand`.
+    pub fn group(&mut self, name: &'static str, description: &'static str) {
+        self.commands
+            .push((name, Definition::Group(description, Vec::new())));
+    }
+
+    pub fn subcommand(
+        &mut self,
+        group: &'static str,
+        name: &'static str,
+        definition: SubcommandDefinition,
+        handler: CommandHandler,
+    ) {
+        match self.commands.iter_mut().find(|(n, _)| *n == group) {
+            Some((_, Definition::Group(_, subcommands))) => subcommands.push(Subcommand {
+                name,
+                definition,
+                handler,
+            }),
+            _ => panic!("Command group {} is not registered", group),
+        }
+    }
+
+    /// Registers the handler for custom IDs whose `ComponentId::kind` is `kind`.
+    pub fn component(&mut self, kind: &'static str, handler: ComponentHandler) {
+        self.components.insert(kind, handler);
+    }
+
+    pub fn modal(&mut self, kind: &'static str, handler: ModalHandler) {
+        self.modals.insert(kind, handler);
+    }
+
+    pub fn autocomplete(&mut self, command: &'static str, handler: AutocompleteHandler) {
+        self.autocompletes.insert(command, handler);
+    }
+
+    fn define<'a>(
+        &self,
+        commands: &'a mut CreateApplicationCommands,
+    ) -> &'a mut CreateApplicationCommands {
+        for (name, definition) in &self.commands {
+            match definition {
+                Definition::Command(definition, _) => {
+                    commands.create_application_command(|command| definition(command));
+                }
+                Definition::Group(description, subcommands) => {
+                    commands.create_application_command(|command| {
+                        command.name(name).description(description);
+                        for subcommand in subcommands {
+                            command.create_option(|option| (subcommand.definition)(option));
+                        }
+                        command
+                    });
+                }
+            }
+        }
+
+        commands
+    }
+
+    fn command_handler(&self, command: &ApplicationCommandInteraction) -> Option<CommandHandler> {
+        let (_, definition) = self
+            .commands
+            .iter()
+            .find(|(name, _)| *name == command.data.name)?;
+
+        match definition {
+            Definition::Command(_, handler) => Some(*handler),
+            Definition::Group(_, subcommands) => {
+                let name = command.data.options.first()?.name.as_str();
+                subcommands
+                    .iter()
+                    .find(|subcommand| subcommand.name == name)
+                    .map(|subcommand| subcommand.handler)
+            }
+        }
+    }
+}
+
+/// Defines every registered command on the guild's command list.
+pub fn define(commands: &mut CreateApplicationCommands) -> &mut CreateApplicationCommands {
+    REGISTRY.define(commands)
+}
+
+/// Routes an interaction to the handler registered for it, logging within a span that
+/// identifies the interaction.
+pub async fn dispatch(api: &dyn DiscordApi, interaction: Interaction) {
+    let (kind, command) = logging::interaction_name(&interaction);
+    let span = logging::interaction_span(&interaction);
+    let started = Instant::now();
+    route(api, interaction).instrument(span).await;
+    metrics::interaction_handled(kind, &command, started.elapsed());
+}
+
+async fn route(api: &dyn DiscordApi, interaction: Interaction) {
+    match interaction {
+        Interaction::ApplicationCommand(command) => {
+            let result = match REGISTRY.command_handler(&command) {
+                Some(handler) => handler(api, &command).await,
+                None => Err(Error::Validation(String::from(
+                    "This command is no longer supported",
+                ))),
+            };
+            if let Err(err) = result {
+                report(api, command.id, &command.token, &command.data.name, err).await;
+            }
+        }
+        Interaction::MessageComponent(component) => {
+            let custom_id = &component.data.custom_id;
+            let result = match decode(custom_id, "button") {
+                Ok(id) => match REGISTRY.components.get(id.kind()) {
+                    Some(handler) => handler(api, &component, id).await,
+                    None => Err(expired("button")),
+                },
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                report(api, component.id, &component.token, custom_id, err).await;
+            }
+        }
+        Interaction::ModalSubmit(modal) => {
+            let custom_id = &modal.data.custom_id;
+            let result = match decode(custom_id, "form") {
+                Ok(id) => match REGISTRY.modals.get(id.kind()) {
+                    Some(handler) => handler(api, &modal, id).await,
+                    None => Err(expired("form")),
+                },
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                report(api, modal.id, &modal.token, custom_id, err).await;
+            }
+        }
+        Interaction::Autocomplete(autocomplete) => {
+            if let Some(handler) = REGISTRY.autocompletes.get(autocomplete.data.name.as_str()) {
+                // Discord has no way to show an error in the suggestion list.
+                if let Err(err) = handler(api, &autocomplete).await {
+                    warn!(error = %err, "Autocomplete failed");
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+/// The result of a handler given a custom ID of a kind it was not registered for.
+pub fn mismatched<'a>(id: ComponentId) -> BoxFuture<'a> {
+    Box::pin(async move {
+        Err(Error::Validation(format!(
+            "This {} component cannot be handled here",
+            id.kind()
+        )))
+    })
+}
+
+/// Sends a response built by a command handler.
+pub async fn respond(
+    api: &dyn DiscordApi,
+    command: &ApplicationCommandInteraction,
+    response: CreateInteractionResponse<'static>,
+) -> Result<()> {
+    api.respond(command.id, &command.token, response).await
+}
+
+/// Answers a slow command with `progress` before Discord's three second deadline, then
+/// replaces it with the response `work` builds. The progress message is removed again if
+/// `work` fails, leaving `dispatch` to report the error.
+pub async fn respond_slowly(
+    api: &dyn DiscordApi,
+    command: &ApplicationCommandInteraction,
+    progress: &str,
+    ephemeral: bool,
+    work: impl Future<Output = Result<EditInteractionResponse>>,
+) -> Result<()> {
+    let mut response = CreateInteractionResponse::default();
+    response
+        .kind(InteractionResponseType::ChannelMessageWithSource)
+        .interaction_response_data(|d| {
+            if ephemeral {
+                d.flags(MessageFlags::EPHEMERAL);
+            }
+            d.content(progress)
+        });
+    api.respond(command.id, &command.token, response).await?;
+
+    match work.await {
+        Ok(response) => api.edit_response(&command.token, response).await,
+        Err(err) => {
+            if let Err(err) = api.delete_response(&command.token).await {
+                warn!(error = %err, "Could not remove progress message");
+            }
+            Err(err)
+        }
+    }
+}
+
+/// Replaces a component's message with `progress` before Discord's three second deadline,
+/// then with the message `work` builds. If `work` fails the message is reset to `retry` so
+/// the user can try again.
+pub async fn update_slowly(
+    api: &dyn DiscordApi,
+    component: &MessageComponentInteraction,
+    progress: &str,
+    retry: EditInteractionResponse,
+    work: impl Future<Output = Result<EditInteractionResponse>>,
+) -> Result<()> {
+    let mut response = CreateInteractionResponse::default();
+    response
+        .kind(InteractionResponseType::UpdateMessage)
+        .interaction_response_data(|d| d.content(progress).components(|c| c));
+    api.respond(component.id, &component.token, response)
+        .await?;
+
+    let (response, result) = match work.await {
+        Ok(response) => (response, Ok(())),
+        Err(err) => (retry, Err(err)),
+    };
+    api.edit_response(&component.token, response).await?;
+    result
+}
+
+/// Logs a failed handler and tells the user what went wrong with an ephemeral message.
+async fn report(
+    api: &dyn DiscordApi,
+    interaction: InteractionId,
+    token: &str,
+    name: &str,
+    err: Error,
+) {
+    match err {
+        Error::Permission(_) | Error::Validation(_) => info!(error = %err, "Rejected {}", name),
+        _ => error!(error = %err, "Handling {} failed", name),
+    }
+    let message = err.user_message();
+    if api
+        .respond(interaction, token, ephemeral_response(&message))
+        .await
+        .is_ok()
+    {
+        return;
+    }
+
+    // The handler already answered before failing.
+    let mut followup = CreateInteractionResponseFollowup::default();
+    followup.content(message).ephemeral(true);
+    if let Err(err) = api.follow_up(token, followup).await {
+        error!(error = %err, "Could not report the failure of {}", name);
+    }
+}
+
+/// Decodes a custom ID, tagging the interaction with the bounty it names.
+fn decode(custom_id: &str, component: &str) -> Result<ComponentId> {
+    let id = ComponentId::decode(custom_id).map_err(|err| {
+        warn!(error = %err, "Rejected custom ID");
+        expired(component)
+    })?;
+    if let Some(bounty) = id.bounty_id() {
+        logging::record_bounty(bounty);
+    }
+    Ok(id)
+}
+
+fn expired(component: &str) -> Error {
+    Error::Validation(format!(
+        "This {} has expired, please run the command again",
+        component
+    ))
+}
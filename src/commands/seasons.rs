@@ -0,0 +1,210 @@
+use std::{env, sync::Arc, sync::Once, time::Duration};
+
+use serenity::{
+    builder::{
+        CreateApplicationCommandOption, CreateEmbed, CreateInteractionResponse, CreateMessage,
+    },
+    model::{
+        prelude::{
+            command::CommandOptionType,
+            interaction::{
+                application_command::ApplicationCommandInteraction, InteractionResponseType,
+            },
+            ChannelId,
+        },
+        Timestamp,
+    },
+};
+
+use tracing::error;
+
+use crate::commands::{
+    bounty::DEFAULT_CURRENCY,
+    leaderboard::{self, Metric},
+    registry::{respond, Registry},
+};
+use crate::discord_util::api::DiscordApi;
+use crate::error::{Error, Result};
+use crate::storage::{
+    seasons::{self, Champion, SeasonResult},
+    store::{self, Store},
+};
+
+const CHAMPIONS: usize = 3;
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+static WATCHER: Once = Once::new();
+
+pub fn register_routes(registry: &mut Registry) {
+    registry.subcommand("bounty", "hall-of-fame", register, |api, command| {
+        Box::pin(respond(api, command, run(command)))
+    });
+}
+
+pub fn register(
+    option: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    option
+        .name("hall-of-fame")
+        .description("Show the champions of past seasons")
+        .kind(CommandOptionType::SubCommand)
+}
+
+pub fn run<'a>(_command: &ApplicationCommandInteraction) -> CreateInteractionResponse<'a> {
+    let results = store::read(|store| store.hall_of_fame.clone());
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Hall of Fame");
+    if results.is_empty() {
+        embed.description("No season has finished yet.");
+    }
+    for result in results.iter().rev().take(25) {
+        embed.field(&result.season.name, summary(result), false);
+    }
+
+    CreateInteractionResponse::default()
+        .kind(InteractionResponseType::ChannelMessageWithSource)
+        .interaction_response_data(|d| d.set_embed(embed))
+        .clone()
+}
+
+/// Starts the background task that closes seasons once their end date has passed.
+pub fn watch(api: Arc<dyn DiscordApi>) {
+    WATCHER.call_once(|| {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                close_finished_seasons(api.as_ref()).await;
+            }
+        });
+    });
+}
+
+async fn close_finished_seasons(api: &dyn DiscordApi) {
+    let seasons = match seasons::all() {
+        Ok(seasons) => seasons,
+        Err(err) => {
+            error!(error = %err, "Could not load seasons");
+            return;
+        }
+    };
+
+    let now = Timestamp::now().unix_timestamp();
+    for season in seasons {
+        // Closed seasons are skipped without a write, which would save the store every tick.
+        let is_closed = |store: &Store| store.hall_of_fame.iter().any(|r| r.season.id == season.id);
+        if season.end.unix_timestamp() > now || store::read(is_closed) {
+            continue;
+        }
+
+        let closed = store::write(|store| {
+            if is_closed(store) {
+                return;
+            }
+
+            let result = SeasonResult {
+                season: season.clone(),
+                completed: champions(store, season.window(), Metric::Completed),
+                earned: champions(store, season.window(), Metric::Earned),
+                rating: champions(store, season.window(), Metric::Rating),
+                announced: false,
+            };
+            store.hall_of_fame.push(result);
+        });
+        if let Err(err) = closed {
+            error!(error = %err, "Could not close season {}", season.id);
+        }
+    }
+
+    // Announcements that failed are retried on the next tick.
+    let unannounced: Vec<SeasonResult> = store::read(|store| {
+        store
+            .hall_of_fame
+            .iter()
+            .filter(|result| !result.announced)
+            .cloned()
+            .collect()
+    });
+    for result in unannounced {
+        if let Err(err) = announce(api, &result).await {
+            error!(error = %err, "Could not post final standings of {}", result.season.id);
+            continue;
+        }
+
+        let marked = store::write(|store| {
+            for stored in &mut store.hall_of_fame {
+                if stored.season.id == result.season.id {
+                    stored.announced = true;
+                }
+            }
+        });
+        if let Err(err) = marked {
+            error!(error = %err, "Could not record announcement of {}", result.season.id);
+        }
+    }
+}
+
+fn champions(store: &Store, window: (i64, i64), metric: Metric) -> Vec<Champion> {
+    leaderboard::standings(store, window, metric, DEFAULT_CURRENCY)
+        .into_iter()
+        .take(CHAMPIONS)
+        .map(|standing| Champion {
+            user: standing.user.id,
+            name: standing.user.name,
+            label: standing.label,
+        })
+        .collect()
+}
+
+/// The channel from `SEASON_CHANNEL` that final standings are posted to, if one is set.
+pub fn season_channel() -> Result<Option<ChannelId>> {
+    match env::var("SEASON_CHANNEL") {
+        Ok(channel) => channel
+            .parse()
+            .map(|id| Some(ChannelId(id)))
+            .map_err(|_| Error::Config(String::from("SEASON_CHANNEL is not a valid ID"))),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Posts the final standings to `SEASON_CHANNEL`, if one is configured.
+async fn announce(api: &dyn DiscordApi, result: &SeasonResult) -> Result<()> {
+    let channel = match season_channel()? {
+        Some(channel) => channel,
+        None => return Ok(()),
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(format!("{} has ended!", result.season.name))
+        .description(summary(result));
+
+    let mut message = CreateMessage::default();
+    message.set_embed(embed);
+    api.send_message(channel, message).await
+}
+
+fn summary(result: &SeasonResult) -> String {
+    let mut summary = String::new();
+    for (metric, champions) in [
+        (Metric::Completed, &result.completed),
+        (Metric::Earned, &result.earned),
+        (Metric::Rating, &result.rating),
+    ] {
+        summary.push_str(&format!("**{}**\n", metric.title()));
+        if champions.is_empty() {
+            summary.push_str("No entries\n");
+        }
+        for (rank, champion) in champions.iter().enumerate() {
+            summary.push_str(&format!(
+                "{}. {} — {}\n",
+                rank + 1,
+                champion.name,
+                champion.label
+            ));
+        }
+    }
+
+    summary
+}
@@ -0,0 +1,122 @@
+use std::env;
+
+use serenity::model::{
+    guild::Member,
+    id::{GuildId, RoleId},
+};
+
+use crate::discord_util::api::DiscordApi;
+use crate::error::{Error, Result};
+
+/// Comma-separated IDs of the roles that see every bounty channel and may look bounties up.
+const STAFF_ROLES: &str = "STAFF_ROLES";
+
+/// Actions only some staff may take. Each defaults to every staff role unless its own
+/// variable lists the roles allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaffAction {
+    /// Completing or reopening a bounty.
+    Complete,
+    /// Declining a bounty on the hunter's behalf.
+    Cancel,
+    /// Closing a bounty with `/bounty force-close`, refunding its reward.
+    ForceClose,
+}
+
+impl StaffAction {
+    const ALL: [StaffAction; 3] = [
+        StaffAction::Complete,
+        StaffAction::Cancel,
+        StaffAction::ForceClose,
+    ];
+
+    fn var(&self) -> &'static str {
+        match self {
+            StaffAction::Complete => "COMPLETE_ROLES",
+            StaffAction::Cancel => "CANCEL_ROLES",
+            StaffAction::ForceClose => "FORCE_CLOSE_ROLES",
+        }
+    }
+}
+
+/// The roles in `STAFF_ROLES`, none if it is unset.
+pub fn staff_roles() -> Result<Vec<RoleId>> {
+    match env::var(STAFF_ROLES) {
+        Ok(roles) => parse_roles(STAFF_ROLES, &roles),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+/// The roles allowed to take `action`.
+pub fn roles_for(action: StaffAction) -> Result<Vec<RoleId>> {
+    match env::var(action.var()) {
+        Ok(roles) => parse_roles(action.var(), &roles),
+        Err(_) => staff_roles(),
+    }
+}
+
+/// Whether `member` has any of the staff roles.
+pub fn is_staff(member: &Option<Member>) -> Result<bool> {
+    Ok(has_any(member, &staff_roles()?))
+}
+
+/// Whether `member` has a role allowed to take `action`.
+pub fn can(member: &Option<Member>, action: StaffAction) -> Result<bool> {
+    Ok(has_any(member, &roles_for(action)?))
+}
+
+/// Fails if an action is granted to a role outside `STAFF_ROLES`. Only staff roles are added
+/// to bounty channels, so members with such a role could not see what they act on.
+pub fn check() -> Result<()> {
+    let staff = staff_roles()?;
+    for action in StaffAction::ALL {
+        if let Some(role) = roles_for(action)?
+            .into_iter()
+            .find(|role| !staff.contains(role))
+        {
+            return Err(Error::Config(format!(
+                "{} contains {}, which is not in {}",
+                action.var(),
+                role,
+                STAFF_ROLES
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The configured staff roles that do not exist in the guild.
+pub async fn missing_roles(api: &dyn DiscordApi, guild_id: GuildId) -> Result<Vec<RoleId>> {
+    let existing: Vec<RoleId> = api
+        .roles(guild_id)
+        .await?
+        .into_iter()
+        .map(|role| role.id)
+        .collect();
+    Ok(staff_roles()?
+        .into_iter()
+        .filter(|role| !existing.contains(role))
+        .collect())
+}
+
+fn has_any(member: &Option<Member>, roles: &[RoleId]) -> bool {
+    member
+        .as_ref()
+        .is_some_and(|member| member.roles.iter().any(|role| roles.contains(role)))
+}
+
+fn parse_roles(var: &str, roles: &str) -> Result<Vec<RoleId>> {
+    roles
+        .split(',')
+        .map(str::trim)
+        .filter(|role| !role.is_empty())
+        .map(|role| {
+            role.parse().map(RoleId).map_err(|_| {
+                Error::Config(format!(
+                    "{} contains {:?}, which is not a role ID",
+                    var, role
+                ))
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use serenity::{
+    async_trait,
+    builder::{
+        CreateAutocompleteResponse, CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateMessage, EditInteractionResponse,
+    },
+    json::{hashmap_to_json_map, Value},
+    model::{
+        guild,
+        id::{ChannelId, GuildId, InteractionId, RoleId, UserId},
+        prelude::{ChannelCategory, ChannelType, GuildChannel, PermissionOverwrite},
+        user::User,
+    },
+};
+
+use crate::error::Result;
+
+/// A guild channel, as far as bounties care about it.
+#[derive(Debug, Clone)]
+pub struct Channel {
+    pub id: ChannelId,
+    pub name: String,
+    pub kind: ChannelType,
+    pub parent_id: Option<ChannelId>,
+    pub overwrites: Vec<PermissionOverwrite>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Role {
+    pub id: RoleId,
+    pub name: String,
+}
+
+impl From<GuildChannel> for Channel {
+    fn from(channel: GuildChannel) -> Channel {
+        Channel {
+            id: channel.id,
+            name: channel.name,
+            kind: channel.kind,
+            parent_id: channel.parent_id,
+            overwrites: channel.permission_overwrites,
+        }
+    }
+}
+
+impl From<ChannelCategory> for Channel {
+    fn from(category: ChannelCategory) -> Channel {
+        Channel {
+            id: category.id,
+            name: category.name,
+            kind: category.kind,
+            parent_id: category.parent_id,
+            overwrites: category.permission_overwrites,
+        }
+    }
+}
+
+impl From<guild::Role> for Role {
+    fn from(role: guild::Role) -> Role {
+        Role {
+            id: role.id,
+            name: role.name,
+        }
+    }
+}
+
+/// A channel to be created.
+#[derive(Debug, Clone)]
+pub struct NewChannel {
+    pub name: String,
+    pub kind: ChannelType,
+    pub parent_id: Option<ChannelId>,
+    pub overwrites: Vec<PermissionOverwrite>,
+}
+
+/// The category called `name` among `channels`.
+pub(crate) fn find_category(channels: &[Channel], name: &str) -> Option<ChannelId> {
+    channels
+        .iter()
+        .find(|channel| channel.kind == ChannelType::Category && channel.name == name)
+        .map(|channel| channel.id)
+}
+
+/// The Discord operations the bot performs, so handlers can run against a fake guild.
+#[async_trait]
+pub trait DiscordApi: Send + Sync {
+    async fn channels(&self, guild_id: GuildId) -> Result<Vec<Channel>>;
+
+    /// The category called `name`, if the guild has one.
+    async fn category(&self, guild_id: GuildId, name: &str) -> Result<Option<ChannelId>> {
+        Ok(find_category(&self.channels(guild_id).await?, name))
+    }
+
+    async fn roles(&self, guild_id: GuildId) -> Result<Vec<Role>>;
+
+    async fn user(&self, user_id: UserId) -> Result<User>;
+
+    async fn create_channel(&self, guild_id: GuildId, channel: NewChannel) -> Result<ChannelId>;
+
+    /// Moves `channel` under the `category` channel.
+    async fn move_channel(&self, channel: ChannelId, category: ChannelId) -> Result<()>;
+
+    /// Replaces every permission overwrite of `channel`.
+    async fn set_overwrites(
+        &self,
+        channel: ChannelId,
+        overwrites: Vec<PermissionOverwrite>,
+    ) -> Result<()>;
+
+    /// Adds or replaces a single permission overwrite of `channel`.
+    async fn add_overwrite(&self, channel: ChannelId, overwrite: PermissionOverwrite)
+        -> Result<()>;
+
+    async fn send_message(&self, channel: ChannelId, message: CreateMessage<'static>)
+        -> Result<()>;
+
+    /// Whether the bot wrote any of the latest messages in `channel`.
+    async fn has_own_message(&self, channel: ChannelId) -> Result<bool>;
+
+    async fn direct_message(&self, user: UserId, message: CreateMessage<'static>) -> Result<()>;
+
+    async fn add_member_role(
+        &self,
+        guild_id: GuildId,
+        user: UserId,
+        role: RoleId,
+        reason: &str,
+    ) -> Result<()>;
+
+    /// Answers an interaction; every interaction must be answered exactly once.
+    async fn respond(
+        &self,
+        interaction: InteractionId,
+        token: &str,
+        response: CreateInteractionResponse<'static>,
+    ) -> Result<()>;
+
+    async fn autocomplete(
+        &self,
+        interaction: InteractionId,
+        token: &str,
+        response: CreateAutocompleteResponse,
+    ) -> Result<()>;
+
+    /// Edits the message an interaction was answered with.
+    async fn edit_response(&self, token: &str, response: EditInteractionResponse) -> Result<()>;
+
+    /// Deletes the message an interaction was answered with.
+    async fn delete_response(&self, token: &str) -> Result<()>;
+
+    /// Sends another message after an interaction was answered.
+    async fn follow_up(
+        &self,
+        token: &str,
+        message: CreateInteractionResponseFollowup<'static>,
+    ) -> Result<()>;
+}
+
+/// The JSON body of one of serenity's builders.
+pub fn to_json(map: HashMap<&'static str, Value>) -> Value {
+    Value::from(hashmap_to_json_map(map))
+}
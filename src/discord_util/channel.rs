@@ -1,204 +1,312 @@
-use serenity::http::Http;
+use std::env;
+
+use serenity::builder::CreateMessage;
 use serenity::model::permissions::Permissions;
 
 use serenity::model::prelude::component::ButtonStyle;
-use serenity::model::prelude::message_component::MessageComponentInteraction;
 use serenity::model::prelude::{
-    ChannelId, ChannelType, GuildChannel, GuildId, PermissionOverwrite, PermissionOverwriteType,
-    UserId,
+    ChannelId, ChannelType, GuildId, PermissionOverwrite, PermissionOverwriteType, RoleId, UserId,
 };
-use serenity::prelude::Context;
+use tracing::warn;
+use uuid::Uuid;
 
-use crate::commands::bounty;
+use crate::commands::{
+    bounty::{self, BountyStatus},
+    component_id::ComponentId,
+    staff,
+};
+use crate::discord_util::api::{DiscordApi, NewChannel};
+use crate::error::{Error, Result};
+use crate::storage::{
+    audit::{AuditAction, AuditEvent},
+    ratings, store,
+};
 
-pub async fn create_category_if_no_exist(http: &Http, guild_id: GuildId, category_name: &str) {
-    let category_id = get_category_id(http, guild_id, category_name).await;
-    if let Some(_) = category_id {
-        return;
+const BOT_USER_ID: UserId = UserId(1110030427869151334);
+
+pub async fn create_category_if_no_exist(
+    api: &dyn DiscordApi,
+    guild_id: GuildId,
+    category_name: &str,
+) -> Result<()> {
+    let category_id = get_category_id(api, guild_id, category_name).await?;
+    if category_id.is_some() {
+        return Ok(());
     }
 
-    let result: Result<GuildChannel, serenity::Error> = guild_id
-        .create_channel(http, |c| c.name(category_name).kind(ChannelType::Category))
-        .await;
+    let category = NewChannel {
+        name: category_name.to_string(),
+        kind: ChannelType::Category,
+        parent_id: None,
+        overwrites: Vec::new(),
+    };
+    api.create_channel(guild_id, category).await?;
+
+    Ok(())
+}
+
+pub async fn get_category_id(
+    api: &dyn DiscordApi,
+    guild_id: GuildId,
+    category_name: &str,
+) -> Result<Option<ChannelId>> {
+    api.category(guild_id, category_name).await
+}
+
+async fn require_category_id(
+    api: &dyn DiscordApi,
+    guild_id: GuildId,
+    category_name: &str,
+) -> Result<ChannelId> {
+    get_category_id(api, guild_id, category_name)
+        .await?
+        .ok_or_else(|| Error::Config(format!("category {} does not exist", category_name)))
+}
 
-    if let Err(err) = result {
-        panic!("Error creating category: {:?}", err);
+/// Creates the channel of a bounty, returning it with the audit event for the caller to save.
+pub async fn create_private_text_channel(
+    api: &dyn DiscordApi,
+    guild_id: GuildId,
+    category_name: &str,
+    bounty: &bounty::Bounty,
+    bounty_id: Uuid,
+) -> Result<(ChannelId, AuditEvent)> {
+    let category_id = require_category_id(api, guild_id, category_name).await?;
+
+    let channel = NewChannel {
+        name: channel_name(bounty),
+        kind: ChannelType::Text,
+        parent_id: Some(category_id),
+        overwrites: participant_permissions(everyone_role(guild_id), bounty),
+    };
+    let channel_id = api.create_channel(guild_id, channel).await?;
+    let event = AuditEvent::new(
+        bounty_id,
+        bounty.bounty_number,
+        AuditAction::ChannelCreated,
+        None,
+    )
+    .channel(channel_id);
+
+    if let Err(err) = send_intro(api, channel_id, bounty, bounty_id).await {
+        warn!(error = %err, "Could not send intro message for bounty");
     }
+    Ok((channel_id, event))
 }
 
-async fn get_category_id(http: &Http, guild_id: GuildId, category_name: &str) -> Option<ChannelId> {
-    let channels = guild_id.channels(http).await;
+/// Posts the message that asks the hunter to accept or decline the bounty.
+pub async fn send_intro(
+    api: &dyn DiscordApi,
+    channel_id: ChannelId,
+    bounty: &bounty::Bounty,
+    bounty_id: Uuid,
+) -> Result<()> {
+    let accept_id = ComponentId::Accept(bounty_id).encode()?;
+    let decline_id = ComponentId::Decline(bounty_id).encode()?;
 
-    if let Ok(channels) = channels {
-        for channel in channels.values() {
-            if channel.kind == ChannelType::Category && channel.name == category_name {
-                return Some(channel.id);
-            }
-        }
+    let reputation = store::read(|store| ratings::reputation(&store.ratings, bounty.hunter.id))
+        .map(|reputation| format!("Hunter reputation: {}", reputation))
+        .unwrap_or_else(|| String::from("Hunter reputation: not rated yet"));
+    let mut intro = format!(
+        "{} would like to start a bounty with you {}. Please accept or decline.\n{}",
+        bounty.lister.name, bounty.hunter.name, reputation
+    );
+    if let Some(description) = &bounty.description {
+        intro.push_str(&format!("\n> {}", description.replace('\n', "\n> ")));
     }
 
-    None
+    let mut message = CreateMessage::default();
+    message.content(intro).components(|c| {
+        c.create_action_row(|r| {
+            r.create_button(|b| {
+                b.style(ButtonStyle::Success)
+                    .label("Accept")
+                    .custom_id(&accept_id)
+            })
+            .create_button(|b| {
+                b.style(ButtonStyle::Danger)
+                    .label("Decline")
+                    .custom_id(&decline_id)
+            })
+        })
+    });
+    api.send_message(channel_id, message).await
 }
 
-pub async fn create_private_text_channel(
-    http: &Http,
+/// Moves a bounty channel to `new_category`. This and the other changes to bounty channels
+/// below return their audit event, if the channel belongs to a bounty, for the caller to save
+/// with the change to the bounty.
+pub async fn switch_category(
+    api: &dyn DiscordApi,
     guild_id: GuildId,
-    category_name: &str,
+    channel: ChannelId,
+    new_category: &str,
+) -> Result<Option<AuditEvent>> {
+    let category = require_category_id(api, guild_id, new_category).await?;
+    api.move_channel(channel, category).await?;
+    Ok(channel_event(
+        channel,
+        AuditAction::ChannelMoved,
+        Some(format!("Moved to {}", new_category)),
+    ))
+}
+
+/// Lets the staff roles see a bounty channel.
+pub async fn add_staff(api: &dyn DiscordApi, channel: ChannelId) -> Result<Option<AuditEvent>> {
+    for role in staff::staff_roles()? {
+        let new_perm = PermissionOverwrite {
+            allow: Permissions::VIEW_CHANNEL,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Role(role),
+        };
+        api.add_overwrite(channel, new_perm).await?;
+    }
+    Ok(channel_event(channel, AuditAction::StaffAdded, None))
+}
+
+pub async fn convert_to_read_only(
+    api: &dyn DiscordApi,
+    guild_id: GuildId,
+    channel: ChannelId,
+) -> Result<Option<AuditEvent>> {
+    api.set_overwrites(channel, read_only_permissions(guild_id)?)
+        .await?;
+    Ok(channel_event(channel, AuditAction::ChannelLocked, None))
+}
+
+pub async fn restore_permissions(
+    api: &dyn DiscordApi,
+    guild_id: GuildId,
+    channel: ChannelId,
     bounty: &bounty::Bounty,
-    id: &str,
-) -> Result<(), String> {
-    let channel_name = format!(
+) -> Result<Option<AuditEvent>> {
+    api.set_overwrites(channel, accepted_permissions(guild_id, bounty)?)
+        .await?;
+    Ok(channel_event(
+        channel,
+        AuditAction::PermissionsRestored,
+        None,
+    ))
+}
+
+/// The audit event of a change to `channel`, against the bounty it belongs to if any.
+fn channel_event(
+    channel: ChannelId,
+    action: AuditAction,
+    detail: Option<String>,
+) -> Option<AuditEvent> {
+    let bounty = store::read(|store| {
+        store
+            .bounties
+            .iter()
+            .find(|(_, bounty)| bounty.channel_id == Some(channel))
+            .map(|(id, bounty)| (*id, bounty.bounty_number))
+    });
+    bounty.map(|(bounty_id, number)| {
+        let mut event = AuditEvent::new(bounty_id, number, action, None).channel(channel);
+        event.detail = detail;
+        event
+    })
+}
+
+/// The name `create_private_text_channel` gives the channel of `bounty`.
+pub fn channel_name(bounty: &bounty::Bounty) -> String {
+    format!(
         "{}-{}-bounty{}",
         bounty.lister.name, bounty.hunter.name, bounty.bounty_number
-    );
+    )
+}
+
+/// The permission overwrites the channel of `bounty` has in its current status.
+pub fn expected_permissions(
+    guild_id: GuildId,
+    bounty: &bounty::Bounty,
+) -> Result<Vec<PermissionOverwrite>> {
+    match bounty.status {
+        BountyStatus::Accepted => accepted_permissions(guild_id, bounty),
+        BountyStatus::Completed | BountyStatus::Cancelled => read_only_permissions(guild_id),
+        BountyStatus::Pending
+        | BountyStatus::Confirming
+        | BountyStatus::Confirmed
+        | BountyStatus::Declined => Ok(participant_permissions(everyone_role(guild_id), bounty)),
+    }
+}
 
-    let category_id: Option<ChannelId> = get_category_id(http, guild_id, category_name).await;
-    if let None = category_id {
-        return Err(String::from("Could not create private text channel"));
+/// Participants and staff can talk.
+fn accepted_permissions(
+    guild_id: GuildId,
+    bounty: &bounty::Bounty,
+) -> Result<Vec<PermissionOverwrite>> {
+    let mut permissions = participant_permissions(everyone_role(guild_id), bounty);
+    for role in staff::staff_roles()? {
+        permissions.push(PermissionOverwrite {
+            allow: Permissions::VIEW_CHANNEL,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Role(role),
+        });
     }
+    Ok(permissions)
+}
 
-    let everyone_role = guild_id
-        .roles(http)
-        .await
-        .unwrap()
-        .values()
-        .find(|&role| role.name == "@everyone")
-        .unwrap()
-        .clone();
-
-    match guild_id
-        .create_channel(http, |channel| {
-            channel
-                .name(channel_name)
-                .kind(ChannelType::Text)
-                .category(category_id.unwrap())
-                .permissions(vec![
-                    PermissionOverwrite {
-                        allow: Permissions::empty(),
-                        deny: Permissions::VIEW_CHANNEL,
-                        kind: PermissionOverwriteType::Role(everyone_role.id),
-                    },
-                    PermissionOverwrite {
-                        allow: Permissions::VIEW_CHANNEL,
-                        deny: Permissions::empty(),
-                        kind: PermissionOverwriteType::Member(UserId(1110030427869151334)), // User ID of the bot
-                    },
-                    PermissionOverwrite {
-                        allow: Permissions::VIEW_CHANNEL,
-                        deny: Permissions::empty(),
-                        kind: PermissionOverwriteType::Member(bounty.lister.id), // User ID of the bounty lister
-                    },
-                    PermissionOverwrite {
-                        allow: Permissions::VIEW_CHANNEL,
-                        deny: Permissions::empty(),
-                        kind: PermissionOverwriteType::Member(bounty.hunter.id), // User ID of the bounty hunter
-                    },
-                ])
-        })
-        .await
-    {
-        Ok(channel) => {
-            let intro = format!(
-                "{} would like to start a bounty with you {}. Please accept or decline.",
-                bounty.lister.name, bounty.hunter.name
-            );
-
-            if let Err(err) = channel
-                .send_message(http, |m| {
-                    m.content(intro).components(|c| {
-                        c.create_action_row(|r| {
-                            r.create_button(|b| {
-                                b.style(ButtonStyle::Success)
-                                    .label("Accept")
-                                    .custom_id(String::from("Accept/") + id)
-                            })
-                            .create_button(|b| {
-                                b.style(ButtonStyle::Danger)
-                                    .label("Decline")
-                                    .custom_id(String::from("Decline/") + id)
-                            })
-                        })
-                    })
-                })
-                .await
-            {
-                eprintln!("Could not send intro message for bounty: {}", err);
-            };
-            Ok(())
-        }
-        Err(err) => Err(err.to_string()),
+/// Only staff can see an archived channel, and nobody can write in it.
+fn read_only_permissions(guild_id: GuildId) -> Result<Vec<PermissionOverwrite>> {
+    let mut permissions = vec![PermissionOverwrite {
+        allow: Permissions::empty(),
+        deny: Permissions::VIEW_CHANNEL,
+        kind: PermissionOverwriteType::Role(everyone_role(guild_id)),
+    }];
+    for role in staff::staff_roles()? {
+        permissions.push(PermissionOverwrite {
+            allow: Permissions::VIEW_CHANNEL,
+            deny: Permissions::SEND_MESSAGES,
+            kind: PermissionOverwriteType::Role(role),
+        });
     }
+    Ok(permissions)
 }
 
-pub async fn switch_category(
-    ctx: &Context,
-    component: &MessageComponentInteraction,
-    new_category: &str,
-) {
-    let archive_category =
-        get_category_id(&ctx.http, component.guild_id.unwrap(), new_category).await;
-    component
-        .channel_id
-        .edit(&ctx.http, |new_channel| {
-            new_channel.category(archive_category)
-        })
-        .await
-        .unwrap();
-}
-
-pub async fn add_ni_team(http: &Http, guild_id: GuildId, channel: ChannelId) {
-    let ni_role = guild_id
-        .roles(http)
-        .await
-        .unwrap()
-        .values()
-        .find(|&role| role.name == "NI Team")
-        .unwrap()
-        .clone();
-
-    let new_perm = PermissionOverwrite {
-        allow: Permissions::VIEW_CHANNEL,
-        deny: Permissions::empty(),
-        kind: PermissionOverwriteType::Role(ni_role.id),
-    };
+/// The staff-only channel named by `STAFF_LOG_CHANNEL`, if one is configured.
+pub fn staff_log_channel() -> Result<Option<ChannelId>> {
+    match env::var("STAFF_LOG_CHANNEL") {
+        Ok(channel) => channel.parse().map(|id| Some(ChannelId(id))).map_err(|_| {
+            Error::Config(format!(
+                "STAFF_LOG_CHANNEL {:?} is not a channel ID",
+                channel
+            ))
+        }),
+        Err(_) => Ok(None),
+    }
+}
 
-    let _ = channel.create_permission(http, &new_perm).await;
-}
-
-pub async fn convert_to_read_only(http: &Http, guild_id: GuildId, channel: ChannelId) {
-    let everyone_role = guild_id
-        .roles(http)
-        .await
-        .unwrap()
-        .values()
-        .find(|&role| role.name == "@everyone")
-        .unwrap()
-        .clone();
-
-    let ni_role = guild_id
-        .roles(http)
-        .await
-        .unwrap()
-        .values()
-        .find(|&role| role.name == "NI Team")
-        .unwrap()
-        .clone();
-
-    channel
-        .edit(http, |c| {
-            c.permissions(vec![
-                PermissionOverwrite {
-                    allow: Permissions::empty(),
-                    deny: Permissions::VIEW_CHANNEL,
-                    kind: PermissionOverwriteType::Role(everyone_role.id),
-                },
-                PermissionOverwrite {
-                    allow: Permissions::VIEW_CHANNEL,
-                    deny: Permissions::SEND_MESSAGES,
-                    kind: PermissionOverwriteType::Role(ni_role.id), // Role ID of the NI Team
-                },
-            ])
-        })
-        .await
-        .unwrap();
+/// Discord gives the @everyone role the ID of its guild.
+fn everyone_role(guild_id: GuildId) -> RoleId {
+    RoleId(guild_id.0)
+}
+
+fn participant_permissions(
+    everyone_role: RoleId,
+    bounty: &bounty::Bounty,
+) -> Vec<PermissionOverwrite> {
+    vec![
+        PermissionOverwrite {
+            allow: Permissions::empty(),
+            deny: Permissions::VIEW_CHANNEL,
+            kind: PermissionOverwriteType::Role(everyone_role),
+        },
+        PermissionOverwrite {
+            allow: Permissions::VIEW_CHANNEL,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(BOT_USER_ID), // User ID of the bot
+        },
+        PermissionOverwrite {
+            allow: Permissions::VIEW_CHANNEL,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(bounty.lister.id), // User ID of the bounty lister
+        },
+        PermissionOverwrite {
+            allow: Permissions::VIEW_CHANNEL,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(bounty.hunter.id), // User ID of the bounty hunter
+        },
+    ]
 }
@@ -0,0 +1,346 @@
+use std::sync::Mutex;
+
+use serde::Serialize;
+use serenity::{
+    async_trait,
+    builder::{
+        CreateAutocompleteResponse, CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateMessage, EditInteractionResponse,
+    },
+    json::{json, Value},
+    model::{
+        id::{ChannelId, GuildId, InteractionId, RoleId, UserId},
+        prelude::{ChannelType, PermissionOverwrite},
+        user::User,
+    },
+};
+
+use crate::discord_util::api::{to_json, Channel, DiscordApi, NewChannel, Role};
+use crate::error::{Error, Result};
+
+const FIRST_ID: u64 = 1_000_000;
+
+/// A request the fake guild received, in the shape it would have been sent to Discord.
+#[derive(Debug, Clone, Serialize)]
+pub struct Call {
+    pub method: &'static str,
+    pub target: String,
+    pub body: Value,
+}
+
+/// An in-memory guild that records every call made to it, for tests.
+pub struct FakeGuild {
+    pub guild_id: GuildId,
+    state: Mutex<State>,
+}
+
+struct State {
+    channels: Vec<Channel>,
+    roles: Vec<Role>,
+    users: Vec<User>,
+    member_roles: Vec<(UserId, RoleId)>,
+    /// Channels the bot sent a message to.
+    posted: Vec<ChannelId>,
+    /// Channels the bot lost access to.
+    denied: Vec<ChannelId>,
+    calls: Vec<Call>,
+    next_id: u64,
+}
+
+impl FakeGuild {
+    /// An empty guild with only the @everyone role, whose ID is the guild ID.
+    pub fn new(guild_id: GuildId) -> FakeGuild {
+        FakeGuild {
+            guild_id,
+            state: Mutex::new(State {
+                channels: Vec::new(),
+                roles: vec![Role {
+                    id: RoleId(guild_id.0),
+                    name: String::from("@everyone"),
+                }],
+                users: Vec::new(),
+                member_roles: Vec::new(),
+                posted: Vec::new(),
+                denied: Vec::new(),
+                calls: Vec::new(),
+                next_id: FIRST_ID,
+            }),
+        }
+    }
+
+    pub fn add_role(&self, name: &str) -> RoleId {
+        let mut state = self.state.lock().unwrap();
+        let id = RoleId(state.next_id());
+        state.roles.push(Role {
+            id,
+            name: name.to_string(),
+        });
+        id
+    }
+
+    pub fn add_category(&self, name: &str) -> ChannelId {
+        let mut state = self.state.lock().unwrap();
+        let id = ChannelId(state.next_id());
+        state.channels.push(Channel {
+            id,
+            name: name.to_string(),
+            kind: ChannelType::Category,
+            parent_id: None,
+            overwrites: Vec::new(),
+        });
+        id
+    }
+
+    pub fn add_text_channel(&self, name: &str, parent_id: Option<ChannelId>) -> ChannelId {
+        let mut state = self.state.lock().unwrap();
+        let id = ChannelId(state.next_id());
+        state.channels.push(Channel {
+            id,
+            name: name.to_string(),
+            kind: ChannelType::Text,
+            parent_id,
+            overwrites: Vec::new(),
+        });
+        id
+    }
+
+    pub fn add_user(&self, user: User) {
+        self.state.lock().unwrap().users.push(user);
+    }
+
+    pub fn channel(&self, id: ChannelId) -> Option<Channel> {
+        let state = self.state.lock().unwrap();
+        state.channels.iter().find(|c| c.id == id).cloned()
+    }
+
+    pub fn channel_by_name(&self, name: &str) -> Option<Channel> {
+        let state = self.state.lock().unwrap();
+        state.channels.iter().find(|c| c.name == name).cloned()
+    }
+
+    /// Fails every later call on `id`, as Discord does once the bot lost access to a channel.
+    pub fn deny_access(&self, id: ChannelId) {
+        self.state.lock().unwrap().denied.push(id);
+    }
+
+    pub fn member_roles(&self, user: UserId) -> Vec<RoleId> {
+        let state = self.state.lock().unwrap();
+        state
+            .member_roles
+            .iter()
+            .filter(|(member, _)| *member == user)
+            .map(|(_, role)| *role)
+            .collect()
+    }
+
+    /// Every call made so far, oldest first.
+    pub fn calls(&self) -> Vec<Call> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    /// Returns and forgets the calls made so far.
+    pub fn take_calls(&self) -> Vec<Call> {
+        std::mem::take(&mut self.state.lock().unwrap().calls)
+    }
+
+    fn record(&self, method: &'static str, target: impl ToString, body: Value) {
+        self.state.lock().unwrap().calls.push(Call {
+            method,
+            target: target.to_string(),
+            body,
+        });
+    }
+
+    fn with_channel<T>(&self, id: ChannelId, f: impl FnOnce(&mut Channel) -> T) -> Result<T> {
+        let mut state = self.state.lock().unwrap();
+        if state.denied.contains(&id) {
+            return Err(unknown("Missing Access"));
+        }
+        match state.channels.iter_mut().find(|c| c.id == id) {
+            Some(channel) => Ok(f(channel)),
+            None => Err(unknown("Unknown Channel")),
+        }
+    }
+}
+
+impl State {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+/// The error Discord answers with when an ID does not exist.
+fn unknown(what: &'static str) -> Error {
+    Error::from(serenity::Error::Other(what))
+}
+
+#[async_trait]
+impl DiscordApi for FakeGuild {
+    async fn channels(&self, guild_id: GuildId) -> Result<Vec<Channel>> {
+        if guild_id != self.guild_id {
+            return Err(unknown("Unknown Guild"));
+        }
+        Ok(self.state.lock().unwrap().channels.clone())
+    }
+
+    async fn roles(&self, guild_id: GuildId) -> Result<Vec<Role>> {
+        if guild_id != self.guild_id {
+            return Err(unknown("Unknown Guild"));
+        }
+        Ok(self.state.lock().unwrap().roles.clone())
+    }
+
+    async fn user(&self, user_id: UserId) -> Result<User> {
+        let state = self.state.lock().unwrap();
+        state
+            .users
+            .iter()
+            .find(|user| user.id == user_id)
+            .cloned()
+            .ok_or_else(|| unknown("Unknown User"))
+    }
+
+    async fn create_channel(&self, guild_id: GuildId, channel: NewChannel) -> Result<ChannelId> {
+        if guild_id != self.guild_id {
+            return Err(unknown("Unknown Guild"));
+        }
+        self.record(
+            "create_channel",
+            guild_id,
+            json!({
+                "name": channel.name,
+                "parent_id": channel.parent_id,
+                "permission_overwrites": channel.overwrites,
+            }),
+        );
+
+        let mut state = self.state.lock().unwrap();
+        let id = ChannelId(state.next_id());
+        state.channels.push(Channel {
+            id,
+            name: channel.name,
+            kind: channel.kind,
+            parent_id: channel.parent_id,
+            overwrites: channel.overwrites,
+        });
+        Ok(id)
+    }
+
+    async fn move_channel(&self, channel: ChannelId, category: ChannelId) -> Result<()> {
+        self.record("move_channel", channel, json!({ "parent_id": category }));
+        self.with_channel(channel, |c| c.parent_id = Some(category))
+    }
+
+    async fn set_overwrites(
+        &self,
+        channel: ChannelId,
+        overwrites: Vec<PermissionOverwrite>,
+    ) -> Result<()> {
+        self.record(
+            "set_overwrites",
+            channel,
+            json!({ "permission_overwrites": overwrites }),
+        );
+        self.with_channel(channel, |c| c.overwrites = overwrites)
+    }
+
+    async fn add_overwrite(
+        &self,
+        channel: ChannelId,
+        overwrite: PermissionOverwrite,
+    ) -> Result<()> {
+        self.record("add_overwrite", channel, json!(overwrite));
+        self.with_channel(channel, |c| {
+            c.overwrites.retain(|o| o.kind != overwrite.kind);
+            c.overwrites.push(overwrite);
+        })
+    }
+
+    async fn send_message(
+        &self,
+        channel: ChannelId,
+        message: CreateMessage<'static>,
+    ) -> Result<()> {
+        self.with_channel(channel, |_| ())?;
+        self.record("send_message", channel, to_json(message.0));
+        self.state.lock().unwrap().posted.push(channel);
+        Ok(())
+    }
+
+    async fn has_own_message(&self, channel: ChannelId) -> Result<bool> {
+        self.with_channel(channel, |_| ())?;
+        Ok(self.state.lock().unwrap().posted.contains(&channel))
+    }
+
+    async fn direct_message(&self, user: UserId, message: CreateMessage<'static>) -> Result<()> {
+        self.record("direct_message", user, to_json(message.0));
+        Ok(())
+    }
+
+    async fn add_member_role(
+        &self,
+        guild_id: GuildId,
+        user: UserId,
+        role: RoleId,
+        reason: &str,
+    ) -> Result<()> {
+        self.record(
+            "add_member_role",
+            user,
+            json!({ "role_id": role, "reason": reason }),
+        );
+        if guild_id != self.guild_id {
+            return Err(unknown("Unknown Guild"));
+        }
+        self.state.lock().unwrap().member_roles.push((user, role));
+        Ok(())
+    }
+
+    async fn respond(
+        &self,
+        interaction: InteractionId,
+        _token: &str,
+        response: CreateInteractionResponse<'static>,
+    ) -> Result<()> {
+        let answered = self
+            .calls()
+            .iter()
+            .any(|call| call.method == "respond" && call.target == interaction.to_string());
+        self.record("respond", interaction, to_json(response.0));
+        if answered {
+            // Discord only accepts one response per interaction.
+            return Err(unknown("Interaction has already been acknowledged."));
+        }
+        Ok(())
+    }
+
+    async fn autocomplete(
+        &self,
+        interaction: InteractionId,
+        _token: &str,
+        response: CreateAutocompleteResponse,
+    ) -> Result<()> {
+        self.record("autocomplete", interaction, to_json(response.0));
+        Ok(())
+    }
+
+    async fn edit_response(&self, token: &str, response: EditInteractionResponse) -> Result<()> {
+        self.record("edit_response", token, to_json(response.0));
+        Ok(())
+    }
+
+    async fn delete_response(&self, token: &str) -> Result<()> {
+        self.record("delete_response", token, Value::Null);
+        Ok(())
+    }
+
+    async fn follow_up(
+        &self,
+        token: &str,
+        message: CreateInteractionResponseFollowup<'static>,
+    ) -> Result<()> {
+        self.record("follow_up", token, to_json(message.0));
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use serenity::model::{
+    channel::ChannelType,
+    guild::Guild,
+    id::{ChannelId, GuildId, RoleId},
+    prelude::Channel as GatewayChannel,
+};
+
+use crate::discord_util::api::{Channel, Role};
+
+/// Roles and channels of every guild the bot is in, kept current from gateway events so
+/// looking them up does not cost an HTTP request each time.
+static CACHE: Lazy<RwLock<HashMap<GuildId, GuildMetadata>>> = Lazy::new(Default::default);
+
+#[derive(Default)]
+struct GuildMetadata {
+    /// `None` until the guild's channels were loaded, or after they went stale.
+    channels: Option<HashMap<ChannelId, Channel>>,
+    roles: Option<HashMap<RoleId, Role>>,
+}
+
+/// A reader or writer that panicked leaves the cache usable, so poisoning is ignored.
+fn read() -> RwLockReadGuard<'static, HashMap<GuildId, GuildMetadata>> {
+    CACHE.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write() -> RwLockWriteGuard<'static, HashMap<GuildId, GuildMetadata>> {
+    CACHE.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Every channel of the guild, if they are cached.
+pub fn channels(guild_id: GuildId) -> Option<Vec<Channel>> {
+    let cache = read();
+    let channels = cache.get(&guild_id)?.channels.as_ref()?;
+    Some(channels.values().cloned().collect())
+}
+
+/// Every role of the guild, if they are cached.
+pub fn roles(guild_id: GuildId) -> Option<Vec<Role>> {
+    let cache = read();
+    let roles = cache.get(&guild_id)?.roles.as_ref()?;
+    Some(roles.values().cloned().collect())
+}
+
+/// The category called `name`, if the guild's channels are cached and it is among them.
+pub fn category_by_name(guild_id: GuildId, name: &str) -> Option<Channel> {
+    let cache = read();
+    let channels = cache.get(&guild_id)?.channels.as_ref()?;
+    channels
+        .values()
+        .find(|channel| channel.kind == ChannelType::Category && channel.name == name)
+        .cloned()
+}
+
+/// Caches channels fetched over HTTP after a miss.
+pub fn set_channels(guild_id: GuildId, channels: Vec<Channel>) {
+    write().entry(guild_id).or_default().channels = Some(
+        channels
+            .into_iter()
+            .map(|channel| (channel.id, channel))
+            .collect(),
+    );
+}
+
+pub fn set_roles(guild_id: GuildId, roles: Vec<Role>) {
+    write().entry(guild_id).or_default().roles =
+        Some(roles.into_iter().map(|role| (role.id, role)).collect());
+}
+
+/// Loads a guild the gateway sent in full, on startup or when the bot joins it.
+pub fn load_guild(guild: Guild) {
+    let channels = guild
+        .channels
+        .into_values()
+        .filter_map(guild_channel)
+        .map(|(_, channel)| channel)
+        .collect();
+    let roles = guild.roles.into_values().map(Role::from).collect();
+    set_channels(guild.id, channels);
+    set_roles(guild.id, roles);
+}
+
+/// Forgets a guild the bot left or that became unavailable.
+pub fn remove_guild(guild_id: GuildId) {
+    write().remove(&guild_id);
+}
+
+/// Records a created or updated channel. Channels of guilds not loaded yet are skipped, as
+/// the next lookup fetches them all anyway.
+pub fn update_channel(guild_id: GuildId, channel: Channel) {
+    if let Some(channels) = write()
+        .get_mut(&guild_id)
+        .and_then(|guild| guild.channels.as_mut())
+    {
+        channels.insert(channel.id, channel);
+    }
+}
+
+pub fn remove_channel(guild_id: GuildId, channel_id: ChannelId) {
+    if let Some(channels) = write()
+        .get_mut(&guild_id)
+        .and_then(|guild| guild.channels.as_mut())
+    {
+        channels.remove(&channel_id);
+    }
+}
+
+/// Drops the cached channels of whichever guild has `channel_id`, for changes the bot made
+/// without getting the updated channel back.
+pub fn invalidate_channel(channel_id: ChannelId) {
+    for guild in write().values_mut() {
+        if guild
+            .channels
+            .as_ref()
+            .is_some_and(|channels| channels.contains_key(&channel_id))
+        {
+            guild.channels = None;
+        }
+    }
+}
+
+pub fn update_role(guild_id: GuildId, role: Role) {
+    if let Some(roles) = write()
+        .get_mut(&guild_id)
+        .and_then(|guild| guild.roles.as_mut())
+    {
+        roles.insert(role.id, role);
+    }
+}
+
+pub fn remove_role(guild_id: GuildId, role_id: RoleId) {
+    if let Some(roles) = write()
+        .get_mut(&guild_id)
+        .and_then(|guild| guild.roles.as_mut())
+    {
+        roles.remove(&role_id);
+    }
+}
+
+/// The guild a gateway channel belongs to, with the channel in the bot's shape.
+pub fn guild_channel(channel: GatewayChannel) -> Option<(GuildId, Channel)> {
+    match channel {
+        GatewayChannel::Guild(channel) => Some((channel.guild_id, channel.into())),
+        GatewayChannel::Category(category) => Some((category.guild_id, category.into())),
+        _ => None,
+    }
+}
@@ -1 +1,7 @@
+pub mod api;
 pub mod channel;
+#[cfg(any(test, feature = "fake"))]
+pub mod fake;
+pub mod guild_cache;
+pub mod reconcile;
+pub mod serenity_api;
@@ -0,0 +1,277 @@
+use serenity::builder::CreateMessage;
+use serenity::model::prelude::{ChannelId, ChannelType, GuildId, PermissionOverwrite};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::commands::bounty::{self, Bounty, BountyStatus};
+use crate::discord_util::{
+    api::{Channel, DiscordApi},
+    channel::{channel_name, expected_permissions, send_intro, staff_log_channel},
+};
+use crate::error::{required_var, Result};
+use crate::storage::store;
+
+/// Discord rejects embed descriptions longer than this.
+const MAX_DESCRIPTION: usize = 4096;
+
+/// What reconciling the bounty store with the guild found and fixed.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Bounties linked to a channel that was created before the bot could save it.
+    pub relinked: Vec<(u32, ChannelId)>,
+    /// Relinked channels the Accept and Decline buttons were posted to again.
+    pub reintroduced: Vec<(u32, ChannelId)>,
+    /// Bounties left confirming with no channel, handed back to the lister to confirm again.
+    pub released: Vec<u32>,
+    /// Bounty channels whose permission overwrites were reset to match the bounty.
+    pub repaired: Vec<(u32, ChannelId)>,
+    /// Bounty channels that could not be repaired or reintroduced, with the reason.
+    pub failed: Vec<(u32, ChannelId, String)>,
+    /// Channels in the bounty categories that belong to no stored bounty.
+    pub orphaned: Vec<ChannelId>,
+    /// Bounties whose channel no longer exists.
+    pub missing: Vec<(u32, ChannelId)>,
+    pub missing_categories: Vec<String>,
+}
+
+impl Report {
+    pub fn is_empty(&self) -> bool {
+        self.relinked.is_empty()
+            && self.reintroduced.is_empty()
+            && self.released.is_empty()
+            && self.repaired.is_empty()
+            && self.failed.is_empty()
+            && self.orphaned.is_empty()
+            && self.missing.is_empty()
+            && self.missing_categories.is_empty()
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for name in &self.missing_categories {
+            lines.push(format!("Category {} does not exist", name));
+        }
+        for (number, channel) in &self.relinked {
+            lines.push(format!("Linked bounty #{} to <#{}>", number, channel));
+        }
+        for (number, channel) in &self.reintroduced {
+            lines.push(format!(
+                "Posted the accept and decline buttons of bounty #{} in <#{}>",
+                number, channel
+            ));
+        }
+        for number in &self.released {
+            lines.push(format!(
+                "Bounty #{} has no channel yet and can be confirmed again",
+                number
+            ));
+        }
+        for (number, channel) in &self.repaired {
+            lines.push(format!(
+                "Repaired permissions of <#{}> (bounty #{})",
+                channel, number
+            ));
+        }
+        for (number, channel, reason) in &self.failed {
+            lines.push(format!(
+                "Could not fix <#{}> (bounty #{}): {}",
+                channel, number, reason
+            ));
+        }
+        for (number, channel) in &self.missing {
+            lines.push(format!(
+                "Channel {} of bounty #{} was deleted",
+                channel, number
+            ));
+        }
+        for channel in &self.orphaned {
+            lines.push(format!("<#{}> belongs to no bounty", channel));
+        }
+        lines
+    }
+}
+
+/// Brings storage and the guild back in line after the bot stopped mid-flow: links bounties
+/// to channels created for them, releases claims whose channel never got created, resets
+/// drifted permission overwrites, and lists channels and records that have no counterpart.
+pub async fn reconcile(api: &dyn DiscordApi, guild_id: GuildId) -> Result<Report> {
+    let mut report = Report::default();
+    let channels = api.channels(guild_id).await?;
+
+    let mut categories = Vec::new();
+    for var in ["BOUNTY_CATEGORY", "ARCHIVE_CATEGORY"] {
+        let name = required_var(var)?;
+        match channels
+            .iter()
+            .find(|channel| channel.kind == ChannelType::Category && channel.name == name)
+        {
+            Some(category) => categories.push((var, category.id)),
+            None => report.missing_categories.push(name),
+        }
+    }
+    let bounty_category = categories
+        .iter()
+        .find(|(var, _)| *var == "BOUNTY_CATEGORY")
+        .map(|(_, id)| *id);
+    let categories: Vec<ChannelId> = categories.into_iter().map(|(_, id)| id).collect();
+    let mut unclaimed: Vec<&Channel> = channels
+        .iter()
+        .filter(|channel| channel.kind != ChannelType::Category)
+        .filter(|channel| {
+            channel
+                .parent_id
+                .is_some_and(|parent| categories.contains(&parent))
+        })
+        .collect();
+
+    let mut bounties: Vec<(Uuid, Bounty)> = store::read(|store| {
+        store
+            .bounties
+            .iter()
+            .map(|(id, bounty)| (*id, bounty.clone()))
+            .collect()
+    });
+    bounties.sort_by_key(|(_, bounty)| {
+        (
+            bounty.created_at.map(|t| t.unix_timestamp()),
+            bounty.bounty_number,
+        )
+    });
+
+    let mut unlinked = Vec::new();
+    for (id, bounty) in bounties {
+        let channel_id = match bounty.channel_id {
+            Some(channel_id) => channel_id,
+            None => {
+                if bounty.status == BountyStatus::Confirming {
+                    unlinked.push((id, bounty));
+                }
+                continue;
+            }
+        };
+
+        match channels.iter().find(|channel| channel.id == channel_id) {
+            Some(channel) => {
+                unclaimed.retain(|unclaimed| unclaimed.id != channel_id);
+                match repair(api, guild_id, channel, &bounty).await {
+                    Ok(true) => report.repaired.push((bounty.bounty_number, channel_id)),
+                    Ok(false) => {}
+                    Err(err) => {
+                        report
+                            .failed
+                            .push((bounty.bounty_number, channel_id, err.to_string()))
+                    }
+                }
+            }
+            None => report.missing.push((bounty.bounty_number, channel_id)),
+        }
+    }
+
+    // A crash between creating a channel and saving it leaves the bounty confirming. Its
+    // channel can only be in the bounty category: an archived channel with the same name
+    // belongs to an older bounty.
+    for (id, bounty) in unlinked {
+        let name = discord_channel_name(&channel_name(&bounty));
+        let position = match unclaimed.iter().position(|channel| {
+            channel.name == name
+                && bounty_category.is_some_and(|category| channel.parent_id == Some(category))
+        }) {
+            Some(position) => position,
+            None => {
+                bounty::release_claim(id);
+                report.released.push(bounty.bounty_number);
+                continue;
+            }
+        };
+        let channel = unclaimed.remove(position);
+        bounty::record_confirmation(id, channel.id, &[])?;
+        report.relinked.push((bounty.bounty_number, channel.id));
+
+        match reintroduce(api, guild_id, channel, id).await {
+            Ok(true) => report.reintroduced.push((bounty.bounty_number, channel.id)),
+            Ok(false) => {}
+            Err(err) => report
+                .failed
+                .push((bounty.bounty_number, channel.id, err.to_string())),
+        }
+    }
+
+    report.orphaned = unclaimed.iter().map(|channel| channel.id).collect();
+    Ok(report)
+}
+
+/// Logs the report and posts it to the staff log channel, if anything was found.
+pub async fn publish(api: &dyn DiscordApi, report: &Report) -> Result<()> {
+    if report.is_empty() {
+        return Ok(());
+    }
+
+    let lines = report.lines();
+    for line in &lines {
+        warn!("Reconciliation: {}", line);
+    }
+
+    let channel = match staff_log_channel()? {
+        Some(channel) => channel,
+        None => return Ok(()),
+    };
+    let mut description = String::new();
+    for line in lines {
+        if description.len() + line.len() + 1 > MAX_DESCRIPTION {
+            break;
+        }
+        description.push_str(&line);
+        description.push('\n');
+    }
+
+    let mut message = CreateMessage::default();
+    message.embed(|e| e.title("Startup reconciliation").description(description));
+    api.send_message(channel, message).await
+}
+
+/// Resets the overwrites of `channel` if they do not match the status of `bounty`.
+async fn repair(
+    api: &dyn DiscordApi,
+    guild_id: GuildId,
+    channel: &Channel,
+    bounty: &Bounty,
+) -> Result<bool> {
+    let expected = expected_permissions(guild_id, bounty)?;
+    if same_overwrites(&channel.overwrites, &expected) {
+        return Ok(false);
+    }
+    api.set_overwrites(channel.id, expected).await?;
+    Ok(true)
+}
+
+/// Sets up a channel that was linked to its bounty after the fact, posting the Accept and
+/// Decline buttons unless the crash came after they were sent. Returns whether they were.
+async fn reintroduce(
+    api: &dyn DiscordApi,
+    guild_id: GuildId,
+    channel: &Channel,
+    bounty_id: Uuid,
+) -> Result<bool> {
+    let bounty = match store::get_bounty(&bounty_id) {
+        Some(bounty) => bounty,
+        None => return Ok(false),
+    };
+    repair(api, guild_id, channel, &bounty).await?;
+    if api.has_own_message(channel.id).await? {
+        return Ok(false);
+    }
+    send_intro(api, channel.id, &bounty, bounty_id).await?;
+    Ok(true)
+}
+
+fn same_overwrites(actual: &[PermissionOverwrite], expected: &[PermissionOverwrite]) -> bool {
+    actual.len() == expected.len() && expected.iter().all(|overwrite| actual.contains(overwrite))
+}
+
+/// Discord lowercases text channel names and replaces spaces with dashes.
+fn discord_channel_name(name: &str) -> String {
+    name.to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+}
@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use serenity::{
+    async_trait,
+    builder::{
+        CreateAutocompleteResponse, CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateMessage, EditInteractionResponse,
+    },
+    http::Http,
+    json::json,
+    model::{
+        id::{ChannelId, GuildId, InteractionId, RoleId, UserId},
+        prelude::PermissionOverwrite,
+        user::User,
+    },
+};
+
+use crate::discord_util::{
+    api::{find_category, to_json, Channel, DiscordApi, NewChannel, Role},
+    guild_cache,
+};
+use crate::error::{Error, Result};
+use crate::metrics;
+
+/// How far back `has_own_message` looks.
+const RECENT_MESSAGES: u64 = 50;
+
+/// Talks to Discord over serenity's HTTP client, reading roles and channels from the guild
+/// cache when it has them.
+pub struct SerenityApi {
+    http: Arc<Http>,
+}
+
+impl SerenityApi {
+    pub fn new(http: Arc<Http>) -> SerenityApi {
+        SerenityApi { http }
+    }
+}
+
+#[async_trait]
+impl DiscordApi for SerenityApi {
+    async fn channels(&self, guild_id: GuildId) -> Result<Vec<Channel>> {
+        if let Some(channels) = guild_cache::channels(guild_id) {
+            return Ok(channels);
+        }
+
+        let channels: Vec<Channel> = guild_id
+            .channels(&self.http)
+            .await
+            .map_err(failed("channels"))?
+            .into_values()
+            .map(Channel::from)
+            .collect();
+        guild_cache::set_channels(guild_id, channels.clone());
+        Ok(channels)
+    }
+
+    async fn category(&self, guild_id: GuildId, name: &str) -> Result<Option<ChannelId>> {
+        if let Some(category) = guild_cache::category_by_name(guild_id, name) {
+            return Ok(Some(category.id));
+        }
+
+        Ok(find_category(&self.channels(guild_id).await?, name))
+    }
+
+    async fn roles(&self, guild_id: GuildId) -> Result<Vec<Role>> {
+        if let Some(roles) = guild_cache::roles(guild_id) {
+            return Ok(roles);
+        }
+
+        let roles: Vec<Role> = guild_id
+            .roles(&self.http)
+            .await
+            .map_err(failed("roles"))?
+            .into_values()
+            .map(Role::from)
+            .collect();
+        guild_cache::set_roles(guild_id, roles.clone());
+        Ok(roles)
+    }
+
+    async fn user(&self, user_id: UserId) -> Result<User> {
+        Ok(user_id.to_user(&self.http).await.map_err(failed("user"))?)
+    }
+
+    async fn create_channel(&self, guild_id: GuildId, channel: NewChannel) -> Result<ChannelId> {
+        let created = guild_id
+            .create_channel(&self.http, |c| {
+                c.name(channel.name)
+                    .kind(channel.kind)
+                    .permissions(channel.overwrites);
+                if let Some(parent_id) = channel.parent_id {
+                    c.category(parent_id);
+                }
+                c
+            })
+            .await
+            .map_err(failed("create_channel"))?;
+        let id = created.id;
+        guild_cache::update_channel(guild_id, created.into());
+        Ok(id)
+    }
+
+    async fn move_channel(&self, channel: ChannelId, category: ChannelId) -> Result<()> {
+        let moved = channel
+            .edit(&self.http, |c| c.category(category))
+            .await
+            .map_err(failed("move_channel"))?;
+        guild_cache::update_channel(moved.guild_id, moved.into());
+        Ok(())
+    }
+
+    async fn set_overwrites(
+        &self,
+        channel: ChannelId,
+        overwrites: Vec<PermissionOverwrite>,
+    ) -> Result<()> {
+        let edited = channel
+            .edit(&self.http, |c| c.permissions(overwrites))
+            .await
+            .map_err(failed("set_overwrites"))?;
+        guild_cache::update_channel(edited.guild_id, edited.into());
+        Ok(())
+    }
+
+    async fn add_overwrite(
+        &self,
+        channel: ChannelId,
+        overwrite: PermissionOverwrite,
+    ) -> Result<()> {
+        channel
+            .create_permission(&self.http, &overwrite)
+            .await
+            .map_err(failed("add_overwrite"))?;
+        guild_cache::invalidate_channel(channel);
+        Ok(())
+    }
+
+    async fn send_message(
+        &self,
+        channel: ChannelId,
+        message: CreateMessage<'static>,
+    ) -> Result<()> {
+        channel
+            .send_message(&self.http, |m| {
+                *m = message;
+                m
+            })
+            .await
+            .map_err(failed("send_message"))?;
+        Ok(())
+    }
+
+    async fn has_own_message(&self, channel: ChannelId) -> Result<bool> {
+        let bot = self
+            .http
+            .get_current_user()
+            .await
+            .map_err(failed("current_user"))?;
+        let messages = channel
+            .messages(&self.http, |m| m.limit(RECENT_MESSAGES))
+            .await
+            .map_err(failed("messages"))?;
+        Ok(messages.iter().any(|message| message.author.id == bot.id))
+    }
+
+    async fn direct_message(&self, user: UserId, message: CreateMessage<'static>) -> Result<()> {
+        let channel = user
+            .create_dm_channel(&self.http)
+            .await
+            .map_err(failed("direct_message"))?;
+        channel
+            .send_message(&self.http, |m| {
+                *m = message;
+                m
+            })
+            .await
+            .map_err(failed("direct_message"))?;
+        Ok(())
+    }
+
+    async fn add_member_role(
+        &self,
+        guild_id: GuildId,
+        user: UserId,
+        role: RoleId,
+        reason: &str,
+    ) -> Result<()> {
+        self.http
+            .add_member_role(guild_id.0, user.0, role.0, Some(reason))
+            .await
+            .map_err(failed("add_member_role"))?;
+        Ok(())
+    }
+
+    async fn respond(
+        &self,
+        interaction: InteractionId,
+        token: &str,
+        response: CreateInteractionResponse<'static>,
+    ) -> Result<()> {
+        self.http
+            .create_interaction_response(interaction.0, token, &to_json(response.0))
+            .await
+            .map_err(failed("respond"))?;
+        Ok(())
+    }
+
+    async fn autocomplete(
+        &self,
+        interaction: InteractionId,
+        token: &str,
+        response: CreateAutocompleteResponse,
+    ) -> Result<()> {
+        // Autocomplete results are interaction response type 8.
+        let response = json!({ "type": 8, "data": to_json(response.0) });
+        self.http
+            .create_interaction_response(interaction.0, token, &response)
+            .await
+            .map_err(failed("autocomplete"))?;
+        Ok(())
+    }
+
+    async fn edit_response(&self, token: &str, response: EditInteractionResponse) -> Result<()> {
+        self.http
+            .edit_original_interaction_response(token, &to_json(response.0))
+            .await
+            .map_err(failed("edit_response"))?;
+        Ok(())
+    }
+
+    async fn delete_response(&self, token: &str) -> Result<()> {
+        self.http
+            .delete_original_interaction_response(token)
+            .await
+            .map_err(failed("delete_response"))?;
+        Ok(())
+    }
+
+    async fn follow_up(
+        &self,
+        token: &str,
+        message: CreateInteractionResponseFollowup<'static>,
+    ) -> Result<()> {
+        self.http
+            .create_followup_message(token, &to_json(message.0))
+            .await
+            .map_err(failed("follow_up"))?;
+        Ok(())
+    }
+}
+
+/// Counts a failed call to Discord under the `DiscordApi` method that made it.
+fn failed(route: &'static str) -> impl FnOnce(serenity::Error) -> Error {
+    move |err| {
+        metrics::discord_error(route);
+        err.into()
+    }
+}
@@ -0,0 +1,70 @@
+use std::{env, fmt, sync::Arc};
+
+use crate::commands::component_id::ComponentIdError;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Why a handler or channel operation failed.
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// A Discord API call failed. Shared because serenity's error is large and not `Clone`.
+    Discord(Arc<serenity::Error>),
+    /// The bounty store could not be saved.
+    Storage(String),
+    /// The user is not allowed to do this; the message is shown to them.
+    Permission(String),
+    /// The request cannot be carried out as given; the message is shown to them.
+    Validation(String),
+    /// A required environment variable is missing or something expected in the guild is not
+    /// there.
+    Config(String),
+    /// The payout provider could not pay the hunter.
+    Payout(String),
+}
+
+impl Error {
+    /// The ephemeral reply the user gets when their interaction fails.
+    pub fn user_message(&self) -> String {
+        match self {
+            Error::Discord(_) => String::from("Discord rejected the request, please try again"),
+            Error::Storage(_) => String::from("Could not save the bounty, please contact staff"),
+            Error::Permission(message) | Error::Validation(message) => message.clone(),
+            Error::Config(_) => {
+                String::from("The bot is not set up correctly, please contact staff")
+            }
+            Error::Payout(_) => String::from("Could not pay the hunter, please contact staff"),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Discord(err) => write!(f, "Discord API error: {}", err),
+            Error::Storage(err) => write!(f, "storage error: {}", err),
+            Error::Permission(err) => write!(f, "permission denied: {}", err),
+            Error::Validation(err) => write!(f, "invalid request: {}", err),
+            Error::Config(err) => write!(f, "configuration error: {}", err),
+            Error::Payout(err) => write!(f, "payout error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serenity::Error> for Error {
+    fn from(err: serenity::Error) -> Error {
+        Error::Discord(Arc::new(err))
+    }
+}
+
+impl From<ComponentIdError> for Error {
+    fn from(err: ComponentIdError) -> Error {
+        Error::Validation(err.to_string())
+    }
+}
+
+/// Reads an environment variable the bot cannot work without.
+pub fn required_var(name: &str) -> Result<String> {
+    env::var(name).map_err(|_| Error::Config(format!("{} is not set", name)))
+}
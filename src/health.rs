@@ -0,0 +1,117 @@
+use std::{
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serenity::{client::bridge::gateway::ShardManager, gateway::ConnectionStage};
+
+use crate::storage::store;
+
+/// How often the shard is asked for its connection stage and heartbeat latency.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+static GATEWAY: Lazy<Mutex<Gateway>> = Lazy::new(|| Mutex::new(Gateway::default()));
+
+/// The shard drops out of `Connected` and reconnects as soon as Discord stops acknowledging
+/// its heartbeats, so its stage alone tells whether the connection is alive.
+#[derive(Default)]
+struct Gateway {
+    stage: Option<ConnectionStage>,
+    ready_at: Option<Instant>,
+    latency: Option<Duration>,
+    /// When the latency was first seen or last changed. Serenity does not expose when the last
+    /// heartbeat was acknowledged, so this only approximates it.
+    heartbeat_at: Option<Instant>,
+}
+
+/// What `/readyz` reports.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub healthy: bool,
+    pub gateway: String,
+    pub seconds_since_ready: Option<u64>,
+    pub latency_ms: Option<u128>,
+    pub seconds_since_heartbeat: Option<u64>,
+    pub storage: String,
+    /// Why the bot is not healthy, empty when it is.
+    pub problems: Vec<String>,
+}
+
+fn gateway() -> MutexGuard<'static, Gateway> {
+    GATEWAY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Records that the gateway sent `Ready`.
+pub fn ready() {
+    let mut gateway = gateway();
+    gateway.stage = Some(ConnectionStage::Connected);
+    gateway.ready_at = Some(Instant::now());
+}
+
+pub fn stage_changed(stage: ConnectionStage) {
+    gateway().stage = Some(stage);
+}
+
+/// Records the latency of the last acknowledged heartbeat, for the report only.
+pub fn heartbeat(latency: Option<Duration>) {
+    let mut gateway = gateway();
+    if latency.is_some() && latency != gateway.latency {
+        gateway.heartbeat_at = Some(Instant::now());
+    }
+    gateway.latency = latency;
+}
+
+/// Follows the connection stage and heartbeats of the bot's shard until the bot exits.
+pub async fn watch(manager: Arc<serenity::prelude::Mutex<ShardManager>>) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let runners = manager.lock().await.runners.clone();
+        let runners = runners.lock().await;
+        // The bot runs a single shard.
+        if let Some(runner) = runners.values().next() {
+            stage_changed(runner.stage);
+            heartbeat(runner.latency);
+        }
+    }
+}
+
+pub fn report() -> Report {
+    let mut problems = Vec::new();
+    let (gateway, since_ready, latency, since_heartbeat) = {
+        let gateway = gateway();
+        (
+            gateway.stage,
+            gateway.ready_at.map(|at| at.elapsed()),
+            gateway.latency,
+            gateway.heartbeat_at.map(|at| at.elapsed()),
+        )
+    };
+
+    match gateway {
+        Some(ConnectionStage::Connected) => (),
+        Some(stage) => problems.push(format!("The gateway is {}", stage)),
+        None => problems.push(String::from("The gateway has not connected yet")),
+    }
+    let storage = match store::check() {
+        Ok(()) => String::from("ok"),
+        Err(err) => {
+            problems.push(err.to_string());
+            String::from("unavailable")
+        }
+    };
+
+    Report {
+        healthy: problems.is_empty(),
+        gateway: gateway
+            .map(|stage| stage.to_string())
+            .unwrap_or_else(|| String::from("not started")),
+        seconds_since_ready: since_ready.map(|elapsed| elapsed.as_secs()),
+        latency_ms: latency.map(|latency| latency.as_millis()),
+        seconds_since_heartbeat: since_heartbeat.map(|elapsed| elapsed.as_secs()),
+        storage,
+        problems,
+    }
+}
@@ -0,0 +1,131 @@
+use std::{convert::Infallible, env, net::SocketAddr, sync::Arc};
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use prometheus::TEXT_FORMAT;
+use tracing::{error, info};
+
+use crate::error::{Error, Result};
+use crate::{health, metrics};
+
+/// What the embedded HTTP server answers besides 404.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    /// `GET /metrics` in the Prometheus text format.
+    Metrics,
+    /// `GET /healthz`, answering while the process runs, and `GET /readyz`, reporting the
+    /// gateway connection and storage with 503 when either is down.
+    Health,
+}
+
+/// The endpoints enabled by `METRICS_ADDR` and `HEALTH_ADDR`, grouped by the address they
+/// are served on. Both are off when their variable is unset.
+pub fn configured() -> Result<Vec<(SocketAddr, Vec<Endpoint>)>> {
+    let mut servers: Vec<(SocketAddr, Vec<Endpoint>)> = Vec::new();
+    for (var, endpoint) in [
+        ("METRICS_ADDR", Endpoint::Metrics),
+        ("HEALTH_ADDR", Endpoint::Health),
+    ] {
+        let address = match self::address(var)? {
+            Some(address) => address,
+            None => continue,
+        };
+        match servers.iter_mut().find(|(served, _)| *served == address) {
+            Some((_, endpoints)) => endpoints.push(endpoint),
+            None => servers.push((address, vec![endpoint])),
+        }
+    }
+    Ok(servers)
+}
+
+/// The address in the environment variable `var`, `None` when the endpoint is disabled.
+pub fn address(var: &str) -> Result<Option<SocketAddr>> {
+    let address = match env::var(var) {
+        Ok(address) => address,
+        Err(_) => return Ok(None),
+    };
+    address
+        .parse()
+        .map(Some)
+        .map_err(|_| Error::Config(format!("could not parse {} {}", var, address)))
+}
+
+/// Serves `endpoints` on `address` until the bot exits.
+pub async fn serve(address: SocketAddr, endpoints: Vec<Endpoint>) {
+    let endpoints = Arc::new(endpoints);
+    let service = make_service_fn(move |_| {
+        let endpoints = endpoints.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let response = handle(&endpoints, request);
+                async move { Ok::<_, Infallible>(response) }
+            }))
+        }
+    });
+
+    let server = match Server::try_bind(&address) {
+        Ok(server) => server,
+        Err(err) => {
+            error!(error = %err, %address, "Could not start HTTP server");
+            return;
+        }
+    };
+    info!(%address, "Serving HTTP");
+    if let Err(err) = server.serve(service).await {
+        error!(error = %err, "HTTP server stopped");
+    }
+}
+
+fn handle(endpoints: &[Endpoint], request: Request<Body>) -> Response<Body> {
+    match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") if endpoints.contains(&Endpoint::Metrics) => {
+            match metrics::render() {
+                Ok(body) => response(StatusCode::OK, TEXT_FORMAT, body),
+                Err(err) => {
+                    error!(error = %err, "Could not render metrics");
+                    response(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "text/plain",
+                        err.to_string(),
+                    )
+                }
+            }
+        }
+        (&Method::GET, "/healthz") if endpoints.contains(&Endpoint::Health) => {
+            response(StatusCode::OK, "text/plain", String::from("ok"))
+        }
+        (&Method::GET, "/readyz") if endpoints.contains(&Endpoint::Health) => {
+            let report = health::report();
+            let status = if report.healthy {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            match serde_json::to_string(&report) {
+                Ok(body) => response(status, "application/json", body),
+                Err(err) => response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "text/plain",
+                    err.to_string(),
+                ),
+            }
+        }
+        _ => response(
+            StatusCode::NOT_FOUND,
+            "text/plain",
+            String::from("Not found"),
+        ),
+    }
+}
+
+fn response(status: StatusCode, content_type: &str, body: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    if let Ok(content_type) = content_type.parse() {
+        response.headers_mut().insert(CONTENT_TYPE, content_type);
+    }
+    response
+}
@@ -0,0 +1,9 @@
+pub mod commands;
+pub mod discord_util;
+pub mod error;
+pub mod health;
+pub mod http;
+pub mod logging;
+pub mod metrics;
+pub mod payout;
+pub mod storage;
@@ -0,0 +1,105 @@
+use std::{env, io};
+
+use serenity::model::prelude::{command::CommandOptionType, interaction::Interaction};
+use tracing::{field, Span};
+use tracing_subscriber::EnvFilter;
+use uuid::Uuid;
+
+use crate::commands::component_id::ComponentId;
+use crate::error::{Error, Result};
+
+const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// Writes logs to stderr at `LOG_LEVEL`, either a level or filter directives such as
+/// `info,serenity=warn`. `LOG_FORMAT=json` writes one JSON object per line, including the
+/// fields of the interaction being handled.
+pub fn init() -> Result<()> {
+    let level = env::var("LOG_LEVEL").unwrap_or_else(|_| String::from(DEFAULT_LOG_LEVEL));
+    let filter = EnvFilter::try_new(&level)
+        .map_err(|err| Error::Config(format!("could not parse LOG_LEVEL {}: {}", level, err)))?;
+    let logs = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr);
+
+    match env::var("LOG_FORMAT").as_deref() {
+        Ok("json") => logs
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .init(),
+        Ok("text") | Err(_) => logs.init(),
+        Ok(format) => {
+            return Err(Error::Config(format!(
+                "unknown LOG_FORMAT {}, expected text or json",
+                format
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// What kind of interaction this is and what it is for: the slash command with its
+/// subcommand, or the kind of component or form.
+pub fn interaction_name(interaction: &Interaction) -> (&'static str, String) {
+    match interaction {
+        Interaction::ApplicationCommand(command) => (
+            "command",
+            match command.data.options.first() {
+                Some(option)
+                    if matches!(
+                        option.kind,
+                        CommandOptionType::SubCommand | CommandOptionType::SubCommandGroup
+                    ) =>
+                {
+                    format!("{} {}", command.data.name, option.name)
+                }
+                _ => command.data.name.clone(),
+            },
+        ),
+        Interaction::MessageComponent(component) => {
+            ("component", custom_id_kind(&component.data.custom_id))
+        }
+        Interaction::ModalSubmit(modal) => ("modal", custom_id_kind(&modal.data.custom_id)),
+        Interaction::Autocomplete(autocomplete) => ("autocomplete", autocomplete.data.name.clone()),
+        Interaction::Ping(_) => ("ping", String::new()),
+    }
+}
+
+/// The span everything done for `interaction` is logged in.
+pub fn interaction_span(interaction: &Interaction) -> Span {
+    let (id, guild, user) = match interaction {
+        Interaction::ApplicationCommand(command) => (command.id, command.guild_id, command.user.id),
+        Interaction::MessageComponent(component) => {
+            (component.id, component.guild_id, component.user.id)
+        }
+        Interaction::ModalSubmit(modal) => (modal.id, modal.guild_id, modal.user.id),
+        Interaction::Autocomplete(autocomplete) => {
+            (autocomplete.id, autocomplete.guild_id, autocomplete.user.id)
+        }
+        Interaction::Ping(ping) => {
+            return tracing::info_span!("interaction", kind = "ping", id = ping.id.0)
+        }
+    };
+    let (kind, command) = interaction_name(interaction);
+
+    tracing::info_span!(
+        "interaction",
+        kind,
+        id = id.0,
+        guild = guild.map(|guild| guild.0),
+        user = user.0,
+        command = %command,
+        bounty_id = field::Empty,
+    )
+}
+
+/// Tags the interaction being handled with the bounty it acts on.
+pub fn record_bounty(id: Uuid) {
+    Span::current().record("bounty_id", field::display(id));
+}
+
+fn custom_id_kind(custom_id: &str) -> String {
+    ComponentId::decode(custom_id)
+        .map(|id| id.kind().to_string())
+        .unwrap_or_else(|_| String::from("unknown"))
+}
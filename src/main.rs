@@ -1,139 +1,204 @@
 use dotenv::dotenv;
-use once_cell::sync::Lazy;
 use serenity::async_trait;
-use serenity::builder::CreateInteractionResponse;
+use serenity::client::bridge::gateway::event::ShardStageUpdateEvent;
 use serenity::framework::StandardFramework;
-use serenity::model::prelude::interaction::{Interaction, InteractionResponseType};
-use serenity::model::prelude::{GuildId, Ready};
+use serenity::model::guild::{Guild, Role, UnavailableGuild};
+use serenity::model::prelude::interaction::Interaction;
+use serenity::model::prelude::{Channel, ChannelCategory, GuildChannel, GuildId, Ready, RoleId};
 use serenity::prelude::{Client, Context, EventHandler, GatewayIntents};
 
 use std::env;
-
-mod commands;
-mod discord_util;
-
-static mut ENV_VARIABLES: Lazy<Vec<&str>> = Lazy::new(|| {
-    let vars = vec!["DISCORD_TOKEN", "GUILD_ID", "BOUNTY_CATEGORY", "NI_ROLE"];
-    vars
-});
+use std::sync::Arc;
+use tracing::{error, info};
+
+use discord_bot_rust::commands;
+use discord_bot_rust::discord_util::{self, guild_cache, serenity_api::SerenityApi};
+use discord_bot_rust::error::required_var;
+use discord_bot_rust::logging;
+use discord_bot_rust::payout::provider;
+use discord_bot_rust::storage::{achievements, seasons, store};
+use discord_bot_rust::{health, http};
+
+/// Variables the bot cannot start without. The rest are optional or have defaults.
+const REQUIRED_VARIABLES: [&str; 4] = [
+    "DISCORD_TOKEN",
+    "GUILD_ID",
+    "BOUNTY_CATEGORY",
+    "ARCHIVE_CATEGORY",
+];
 
 struct Handler;
 
 #[async_trait]
 impl EventHandler for Handler {
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        match interaction {
-            Interaction::ApplicationCommand(command) => {
-                let content = match command.data.name.as_str() {
-                    "bounty" => commands::bounty::run(&command),
-                    _ => CreateInteractionResponse::default()
-                        .kind(InteractionResponseType::ChannelMessageWithSource)
-                        .clone(),
-                };
-
-                if let Err(err) = command
-                    .create_interaction_response(&ctx.http, |r| {
-                        *r = content.clone();
-                        r
-                    })
-                    .await
-                {
-                    eprintln!("Failed to ask for confirmation: {:?}", err);
-                }
-            }
-            Interaction::MessageComponent(component) => {
-                let ind = component
-                    .data
-                    .custom_id
-                    .find('/')
-                    .unwrap_or_else(|| component.data.custom_id.len());
-                let command = &component.data.custom_id[..ind];
-                let id = &component.data.custom_id[ind + 1..];
-                match command {
-                    "bounty" => {
-                        let res = commands::bounty::confirm_bounty(&ctx.http, &component).await;
-                        match res {
-                            Ok(_) => {
-                                if let Err(err) = component
-                                    .create_interaction_response(&ctx.http, |r| {
-                                        r.kind(InteractionResponseType::UpdateMessage)
-                                            .interaction_response_data(|d| {
-                                                d.content("Confirmed").components(|c| c)
-                                            })
-                                    })
-                                    .await
-                                {
-                                    eprintln!("Failed to confirm bounty: {:?}", err);
-                                }
-                            }
-                            Err(err) => {
-                                eprintln!("Err: {}", err);
-                            }
-                        }
-                    }
-                    "Accept" => commands::bounty::accept(&ctx.http, &component, id).await,
-                    "Decline" => commands::bounty::decline(&ctx.http, &component).await,
-                    "Complete" => commands::bounty::complete(&ctx, &component).await,
-                    _ => eprintln!("Uknown button id"),
-                }
-            }
-            _ => (),
+        let api = SerenityApi::new(ctx.http.clone());
+        commands::registry::dispatch(&api, interaction).await;
+    }
+
+    async fn shard_stage_update(&self, _ctx: Context, event: ShardStageUpdateEvent) {
+        health::stage_changed(event.new);
+    }
+
+    async fn guild_create(&self, _ctx: Context, guild: Guild, _is_new: bool) {
+        guild_cache::load_guild(guild);
+    }
+
+    async fn guild_delete(&self, _ctx: Context, guild: UnavailableGuild, _full: Option<Guild>) {
+        guild_cache::remove_guild(guild.id);
+    }
+
+    async fn channel_create(&self, _ctx: Context, channel: &GuildChannel) {
+        guild_cache::update_channel(channel.guild_id, channel.clone().into());
+    }
+
+    async fn category_create(&self, _ctx: Context, category: &ChannelCategory) {
+        guild_cache::update_channel(category.guild_id, category.clone().into());
+    }
+
+    async fn channel_update(&self, _ctx: Context, _old: Option<Channel>, new: Channel) {
+        if let Some((guild_id, channel)) = guild_cache::guild_channel(new) {
+            guild_cache::update_channel(guild_id, channel);
         }
     }
 
+    async fn channel_delete(&self, _ctx: Context, channel: &GuildChannel) {
+        guild_cache::remove_channel(channel.guild_id, channel.id);
+    }
+
+    async fn category_delete(&self, _ctx: Context, category: &ChannelCategory) {
+        guild_cache::remove_channel(category.guild_id, category.id);
+    }
+
+    async fn guild_role_create(&self, _ctx: Context, role: Role) {
+        guild_cache::update_role(role.guild_id, role.into());
+    }
+
+    async fn guild_role_update(&self, _ctx: Context, _old: Option<Role>, role: Role) {
+        guild_cache::update_role(role.guild_id, role.into());
+    }
+
+    async fn guild_role_delete(
+        &self,
+        _ctx: Context,
+        guild_id: GuildId,
+        role_id: RoleId,
+        _role: Option<Role>,
+    ) {
+        guild_cache::remove_role(guild_id, role_id);
+    }
+
     async fn ready(&self, ctx: Context, bot: Ready) {
-        unsafe {
-            for env_var in ENV_VARIABLES.iter() {
-                if let Err(_) = env::var(*env_var) {
-                    panic!("Environment variable {} is not set", *env_var);
-                }
+        health::ready();
+        info!("Connected as {}#{}", bot.user.name, bot.user.discriminator);
+
+        let guild_id = match commands::bounty::configured_guild() {
+            Ok(guild_id) => guild_id,
+            Err(err) => {
+                error!(error = %err, "Could not find the guild");
+                return;
             }
+        };
+
+        if let Err(err) =
+            GuildId::set_application_commands(&guild_id, &ctx.http, commands::registry::define)
+                .await
+        {
+            error!(error = %err, "Could not register commands");
         }
 
-        println!("Connected as {}#{}", bot.user.name, bot.user.discriminator);
+        let api = Arc::new(SerenityApi::new(ctx.http.clone()));
+        for category in ["BOUNTY_CATEGORY", "ARCHIVE_CATEGORY"] {
+            let category_name = match required_var(category) {
+                Ok(category_name) => category_name,
+                Err(err) => {
+                    error!(error = %err, "Could not create the categories");
+                    continue;
+                }
+            };
+            if let Err(err) = discord_util::channel::create_category_if_no_exist(
+                api.as_ref(),
+                guild_id,
+                &category_name,
+            )
+            .await
+            {
+                error!(error = %err, "Could not create category {}", category_name);
+            }
+        }
 
-        let guild_id = GuildId(
-            env::var("GUILD_ID")
-                .expect("GUILD_ID not set.")
-                .parse()
-                .expect("Could not parse GUILD_ID"),
-        );
+        match discord_util::reconcile::reconcile(api.as_ref(), guild_id).await {
+            Ok(report) => {
+                if let Err(err) = discord_util::reconcile::publish(api.as_ref(), &report).await {
+                    error!(error = %err, "Could not publish reconciliation report");
+                }
+            }
+            Err(err) => error!(error = %err, "Could not reconcile bounties"),
+        }
 
-        if let Err(err) = GuildId::set_application_commands(&guild_id, &ctx.http, |commands| {
-            commands.create_application_command(|command| commands::bounty::register(command))
-        })
-        .await
-        {
-            panic!("Could not register commands. {}", err);
-        };
+        match commands::staff::missing_roles(api.as_ref(), guild_id).await {
+            Ok(missing) => {
+                for role in missing {
+                    error!("Staff role {} does not exist in the guild", role);
+                }
+            }
+            Err(err) => error!(error = %err, "Could not check the staff roles"),
+        }
 
-        let category_name: String = env::var("BOUNTY_CATEGORY").expect("Bounty Category not set.");
-        discord_util::channel::create_category_if_no_exist(&ctx.http, guild_id, &category_name)
-            .await;
+        commands::seasons::watch(api);
+    }
+}
 
-        let category_name: String =
-            env::var("ARCHIVE_CATEGORY").expect("Archive Category not set.");
-        discord_util::channel::create_category_if_no_exist(&ctx.http, guild_id, &category_name)
-            .await;
+/// Loads the data file and every optional config up front, so a mistake in them stops the bot
+/// here instead of failing the first interaction that needs it.
+fn check_config() -> discord_bot_rust::error::Result<()> {
+    for var in REQUIRED_VARIABLES {
+        required_var(var)?;
     }
+    commands::bounty::configured_guild()?;
+    store::init()?;
+    commands::staff::check()?;
+    provider::configured()?;
+    achievements::milestones()?;
+    seasons::all()?;
+    commands::seasons::season_channel()?;
+    http::configured()?;
+    Ok(())
 }
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
+    // Nothing can be logged before logging is set up, so its config errors go to stderr.
+    if let Err(err) = logging::init() {
+        eprintln!("Invalid configuration: {}", err);
+        std::process::exit(1);
+    }
+
+    if let Err(err) = check_config() {
+        error!(error = %err, "Invalid configuration");
+        std::process::exit(1);
+    }
 
     // Login with a bot token from the environment
     let token = env::var("DISCORD_TOKEN").expect("DISCORD_TOKEN not set.");
 
     let framework = StandardFramework::new().configure(|c| c.prefix("/"));
 
-    let intents = GatewayIntents::default();
+    // GUILDS keeps the guild cache current with role and channel events.
+    let intents = GatewayIntents::GUILDS;
     let mut client = Client::builder(token, intents)
         .event_handler(Handler)
         .framework(framework)
         .await
         .expect("Error creating client");
 
+    // Checked by check_config.
+    for (address, endpoints) in http::configured().unwrap_or_default() {
+        tokio::spawn(http::serve(address, endpoints));
+    }
+    tokio::spawn(health::watch(client.shard_manager.clone()));
+
     // start listening for events by starting a single shard
     if let Err(err) = client.start().await {
         panic!("An error occurred while starting the client: {:?}", err);
@@ -0,0 +1,94 @@
+use std::{env, time::Duration};
+
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec, Encoder,
+    HistogramVec, IntCounterVec, IntGaugeVec, TextEncoder,
+};
+
+use crate::commands::bounty::BountyStatus;
+use crate::storage::{events::EventKind, store};
+
+static BOUNTY_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "bounty_events_total",
+        "Bounty state changes by event",
+        &["event"]
+    )
+    .unwrap()
+});
+
+static INTERACTION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "interaction_duration_seconds",
+        "Time taken to handle an interaction, including its Discord calls",
+        &["kind", "command"]
+    )
+    .unwrap()
+});
+
+static DISCORD_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "discord_api_errors_total",
+        "Failed Discord API calls by route",
+        &["route"]
+    )
+    .unwrap()
+});
+
+static OPEN_BOUNTIES: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "open_bounties",
+        "Bounties that are not completed, declined or cancelled",
+        &["guild", "status"]
+    )
+    .unwrap()
+});
+
+const OPEN_STATUSES: [BountyStatus; 4] = [
+    BountyStatus::Pending,
+    BountyStatus::Confirming,
+    BountyStatus::Confirmed,
+    BountyStatus::Accepted,
+];
+
+pub fn bounty_event(kind: &EventKind) {
+    BOUNTY_EVENTS.with_label_values(&[kind.as_str()]).inc();
+}
+
+pub fn interaction_handled(kind: &str, command: &str, duration: Duration) {
+    INTERACTION_SECONDS
+        .with_label_values(&[kind, command])
+        .observe(duration.as_secs_f64());
+}
+
+/// Counts a failed Discord call, `route` being the `DiscordApi` method that made it.
+pub fn discord_error(route: &str) {
+    DISCORD_ERRORS.with_label_values(&[route]).inc();
+}
+
+/// Every metric in the Prometheus text format. Open bounties are counted from the store
+/// when scraped; they all belong to the guild the bot serves.
+pub fn render() -> Result<String, prometheus::Error> {
+    let guild = env::var("GUILD_ID").unwrap_or_default();
+    let open = store::read(|store| {
+        OPEN_STATUSES.map(|status| {
+            let count = store
+                .bounties
+                .values()
+                .filter(|bounty| bounty.status == status)
+                .count();
+            (status, count)
+        })
+    });
+    for (status, count) in open {
+        OPEN_BOUNTIES
+            .with_label_values(&[&guild, status.as_str()])
+            .set(count as i64);
+    }
+
+    let mut buffer = Vec::new();
+    TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+    String::from_utf8(buffer)
+        .map_err(|err| prometheus::Error::Msg(format!("metrics are not valid UTF-8: {}", err)))
+}
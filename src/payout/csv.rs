@@ -0,0 +1,87 @@
+use serenity::{async_trait, model::user::User, model::Timestamp};
+use std::{
+    env,
+    fs::OpenOptions,
+    io::{self, Write},
+    path::Path,
+};
+use uuid::Uuid;
+
+use crate::commands::bounty::Bounty;
+use crate::error::{Error, Result};
+use crate::payout::provider::PayoutProvider;
+
+const DEFAULT_CSV_FILE: &str = "payouts.csv";
+const HEADER: &str =
+    "timestamp,reference,bounty_id,bounty_number,hunter_id,hunter_name,amount,currency";
+
+/// Appends every payout to a CSV file for staff to settle off-platform.
+pub struct CsvProvider {
+    path: String,
+}
+
+impl CsvProvider {
+    pub fn new(path: String) -> CsvProvider {
+        CsvProvider { path }
+    }
+
+    pub fn from_env() -> CsvProvider {
+        CsvProvider::new(
+            env::var("PAYOUT_CSV_FILE").unwrap_or_else(|_| String::from(DEFAULT_CSV_FILE)),
+        )
+    }
+
+    /// Appends `row`, writing the header first if the file is new.
+    fn append(&self, row: &[String]) -> io::Result<()> {
+        let is_new = !Path::new(&self.path).exists();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        if is_new {
+            writeln!(file, "{}", HEADER)?;
+        }
+
+        let row: Vec<String> = row.iter().map(|field| quote(field)).collect();
+        writeln!(file, "{}", row.join(","))
+    }
+}
+
+#[async_trait]
+impl PayoutProvider for CsvProvider {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    async fn pay(
+        &self,
+        bounty_id: Uuid,
+        bounty: &Bounty,
+        hunter: &User,
+        amount: u64,
+        currency: &str,
+    ) -> Result<String> {
+        let reference = Uuid::new_v4().to_string();
+        let row = [
+            Timestamp::now().to_string(),
+            reference.clone(),
+            bounty_id.to_string(),
+            bounty.bounty_number.to_string(),
+            hunter.id.to_string(),
+            hunter.name.clone(),
+            amount.to_string(),
+            currency.to_string(),
+        ];
+        self.append(&row)
+            .map_err(|err| Error::Payout(format!("could not write {}: {}", self.path, err)))?;
+
+        Ok(reference)
+    }
+}
+
+/// Quotes a field as RFC 4180 asks, so names holding commas, quotes or line breaks stay in
+/// their column.
+fn quote(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
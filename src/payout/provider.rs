@@ -0,0 +1,61 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serenity::{async_trait, model::user::User};
+use std::env;
+use uuid::Uuid;
+
+use crate::commands::bounty::Bounty;
+use crate::error::{self, Error};
+use crate::payout::{csv::CsvProvider, webhook::WebhookProvider};
+
+static PAYOUT_PROVIDER: Lazy<error::Result<Option<Box<dyn PayoutProvider>>>> = Lazy::new(from_env);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PayoutStatus {
+    Pending,
+    Paid,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Payout {
+    pub provider: String,
+    pub status: PayoutStatus,
+    pub reference: Option<String>,
+}
+
+/// Sends the reward of a completed bounty to its hunter.
+#[async_trait]
+pub trait PayoutProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Pays `amount` of `currency` to `hunter` and returns the provider's reference ID.
+    async fn pay(
+        &self,
+        bounty_id: Uuid,
+        bounty: &Bounty,
+        hunter: &User,
+        amount: u64,
+        currency: &str,
+    ) -> error::Result<String>;
+}
+
+/// The provider selected by `PAYOUT_PROVIDER`, or `None` when hunters are paid manually.
+pub fn configured() -> error::Result<Option<&'static dyn PayoutProvider>> {
+    match &*PAYOUT_PROVIDER {
+        Ok(provider) => Ok(provider.as_deref()),
+        Err(err) => Err(err.clone()),
+    }
+}
+
+fn from_env() -> error::Result<Option<Box<dyn PayoutProvider>>> {
+    let provider = match env::var("PAYOUT_PROVIDER") {
+        Ok(provider) => provider,
+        Err(_) => return Ok(None),
+    };
+    match provider.as_str() {
+        "csv" => Ok(Some(Box::new(CsvProvider::from_env()))),
+        "webhook" => Ok(Some(Box::new(WebhookProvider::from_env()?))),
+        other => Err(Error::Config(format!("unknown payout provider {}", other))),
+    }
+}
@@ -0,0 +1,84 @@
+use serde::Deserialize;
+use serde_json::json;
+use serenity::{async_trait, model::user::User};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::commands::bounty::Bounty;
+use crate::error::{self, required_var, Error};
+use crate::payout::provider::PayoutProvider;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// A payout blocks the completion follow-up, so a stuck endpoint fails it instead.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Posts every payout as JSON to an HTTP endpoint that performs the transfer.
+pub struct WebhookProvider {
+    url: String,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct WebhookResponse {
+    reference: String,
+}
+
+impl WebhookProvider {
+    pub fn new(url: String) -> error::Result<WebhookProvider> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|err| Error::Config(format!("Could not create payout client: {}", err)))?;
+
+        Ok(WebhookProvider { url, client })
+    }
+
+    pub fn from_env() -> error::Result<WebhookProvider> {
+        WebhookProvider::new(required_var("PAYOUT_WEBHOOK_URL")?)
+    }
+}
+
+#[async_trait]
+impl PayoutProvider for WebhookProvider {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn pay(
+        &self,
+        bounty_id: Uuid,
+        bounty: &Bounty,
+        hunter: &User,
+        amount: u64,
+        currency: &str,
+    ) -> error::Result<String> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "bounty_id": bounty_id,
+                "bounty_number": bounty.bounty_number,
+                "hunter_id": hunter.id,
+                "hunter_name": hunter.name,
+                "amount": amount,
+                "currency": currency,
+            }))
+            .send()
+            .await
+            .map_err(|err| Error::Payout(err.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(Error::Payout(format!(
+                "webhook returned {}",
+                response.status()
+            )));
+        }
+
+        response
+            .json::<WebhookResponse>()
+            .await
+            .map(|body| body.reference)
+            .map_err(|err| Error::Payout(format!("unexpected webhook response: {}", err)))
+    }
+}
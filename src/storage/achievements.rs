@@ -0,0 +1,99 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serenity::model::{
+    id::{RoleId, UserId},
+    Timestamp,
+};
+use std::{env, fs};
+use uuid::Uuid;
+
+use crate::commands::bounty::BountyStatus;
+use crate::error::{Error, Result};
+use crate::storage::store::Store;
+
+static MILESTONES: Lazy<Result<Vec<Milestone>>> = Lazy::new(load_milestones);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    /// The hunter completed at least this many bounties.
+    Completed(usize),
+    /// The hunter's most recent ratings are this many 5-star ratings in a row.
+    FiveStarStreak(usize),
+}
+
+/// An achievement as defined in the `ACHIEVEMENTS_FILE` config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Milestone {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub rule: Rule,
+    pub role_id: Option<RoleId>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Unlock {
+    pub user: UserId,
+    pub milestone: String,
+    pub bounty_id: Uuid,
+    pub timestamp: Timestamp,
+}
+
+fn load_milestones() -> Result<Vec<Milestone>> {
+    let path = match env::var("ACHIEVEMENTS_FILE") {
+        Ok(path) => path,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|err| Error::Config(format!("could not read {}: {}", path, err)))?;
+    serde_json::from_str(&contents)
+        .map_err(|err| Error::Config(format!("could not parse {}: {}", path, err)))
+}
+
+/// The milestones defined in `ACHIEVEMENTS_FILE`, none if it is not set.
+pub fn milestones() -> Result<&'static [Milestone]> {
+    MILESTONES.as_deref().map_err(|err| err.clone())
+}
+
+/// Records every milestone `hunter` reached but has not unlocked yet and returns them.
+pub fn unlock_new(store: &mut Store, hunter: UserId, bounty_id: Uuid) -> Result<Vec<Milestone>> {
+    let completed = store
+        .bounties
+        .values()
+        .filter(|b| b.hunter.id == hunter && b.status == BountyStatus::Completed)
+        .count();
+
+    let mut ratings: Vec<_> = store
+        .ratings
+        .iter()
+        .filter(|r| r.hunter == hunter)
+        .collect();
+    ratings.sort_by_key(|r| std::cmp::Reverse(r.timestamp.unix_timestamp()));
+    let streak = ratings.iter().take_while(|r| r.score == 5).count();
+
+    let mut unlocked = Vec::new();
+    for milestone in milestones()? {
+        let reached = match milestone.rule {
+            Rule::Completed(count) => completed >= count,
+            Rule::FiveStarStreak(count) => streak >= count,
+        };
+        let already = store
+            .achievements
+            .iter()
+            .any(|u| u.user == hunter && u.milestone == milestone.id);
+
+        if reached && !already {
+            store.achievements.push(Unlock {
+                user: hunter,
+                milestone: milestone.id.clone(),
+                bounty_id,
+                timestamp: Timestamp::now(),
+            });
+            unlocked.push(milestone.clone());
+        }
+    }
+
+    Ok(unlocked)
+}
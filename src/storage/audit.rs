@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use serenity::model::{
+    id::{ChannelId, UserId},
+    Timestamp,
+};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditAction {
+    Started,
+    Confirmed,
+    Accepted,
+    Declined,
+    Completed,
+    Reopened,
+    ForceClosed,
+    /// The private channel of the bounty was created.
+    ChannelCreated,
+    /// The channel was moved to another category.
+    ChannelMoved,
+    /// Staff were given access to the channel.
+    StaffAdded,
+    /// The channel was made read-only on archiving.
+    ChannelLocked,
+    /// Participants could write in the channel again after reopening.
+    PermissionsRestored,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Started => "started",
+            AuditAction::Confirmed => "confirmed",
+            AuditAction::Accepted => "accepted",
+            AuditAction::Declined => "declined",
+            AuditAction::Completed => "completed",
+            AuditAction::Reopened => "reopened",
+            AuditAction::ForceClosed => "force-closed",
+            AuditAction::ChannelCreated => "channel created",
+            AuditAction::ChannelMoved => "channel moved",
+            AuditAction::StaffAdded => "staff added",
+            AuditAction::ChannelLocked => "channel locked",
+            AuditAction::PermissionsRestored => "permissions restored",
+        }
+    }
+}
+
+/// Something that happened to a bounty, kept so staff can tell who did what.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub bounty_id: Uuid,
+    pub bounty_number: u32,
+    pub action: AuditAction,
+    /// The user whose interaction caused the event, `None` when the bot acted on its own.
+    pub actor: Option<UserId>,
+    pub channel_id: Option<ChannelId>,
+    pub detail: Option<String>,
+    pub timestamp: Timestamp,
+}
+
+impl AuditEvent {
+    pub fn new(
+        bounty_id: Uuid,
+        bounty_number: u32,
+        action: AuditAction,
+        actor: Option<UserId>,
+    ) -> AuditEvent {
+        AuditEvent {
+            bounty_id,
+            bounty_number,
+            action,
+            actor,
+            channel_id: None,
+            detail: None,
+            timestamp: Timestamp::now(),
+        }
+    }
+
+    pub fn channel(mut self, channel_id: ChannelId) -> AuditEvent {
+        self.channel_id = Some(channel_id);
+        self
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> AuditEvent {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// The events of one bounty, oldest first.
+pub fn timeline<'a>(events: &'a [AuditEvent], bounty_id: &Uuid) -> Vec<&'a AuditEvent> {
+    let mut timeline: Vec<&AuditEvent> = events
+        .iter()
+        .filter(|event| event.bounty_id == *bounty_id)
+        .collect();
+    timeline.sort_by_key(|event| event.timestamp.unix_timestamp());
+    timeline
+}
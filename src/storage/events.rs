@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use serenity::model::{id::ChannelId, Timestamp};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::commands::bounty::{Bounty, BountyStatus};
+use crate::payout::provider::Payout;
+
+/// A change to a bounty. Bounties are never edited in place: their current state is the
+/// result of applying every event in order, so it can be rebuilt from the history at will.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BountyEvent {
+    pub bounty_id: Uuid,
+    pub timestamp: Timestamp,
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum EventKind {
+    Created {
+        bounty: Box<Bounty>,
+    },
+    /// The lister confirmed the bounty and its private channel is being created.
+    Confirming,
+    /// Creating the channel failed, the bounty is pending again.
+    ConfirmationFailed,
+    /// The private channel of the bounty was created.
+    Confirmed {
+        channel_id: ChannelId,
+    },
+    Accepted,
+    Declined,
+    Completed,
+    /// Staff moved a completed bounty back to accepted.
+    Reopened,
+    /// Staff closed the bounty with `/bounty force-close`.
+    Cancelled,
+    PayoutUpdated {
+        payout: Payout,
+    },
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Created { .. } => "created",
+            EventKind::Confirming => "confirming",
+            EventKind::ConfirmationFailed => "confirmation_failed",
+            EventKind::Confirmed { .. } => "confirmed",
+            EventKind::Accepted => "accepted",
+            EventKind::Declined => "declined",
+            EventKind::Completed => "completed",
+            EventKind::Reopened => "reopened",
+            EventKind::Cancelled => "cancelled",
+            EventKind::PayoutUpdated { .. } => "payout_updated",
+        }
+    }
+}
+
+impl BountyEvent {
+    pub fn new(bounty_id: Uuid, kind: EventKind) -> BountyEvent {
+        BountyEvent {
+            bounty_id,
+            timestamp: Timestamp::now(),
+            kind,
+        }
+    }
+}
+
+/// Applies `event` to the bounties it was recorded against.
+pub fn apply(bounties: &mut HashMap<Uuid, Bounty>, event: &BountyEvent) {
+    if let EventKind::Created { bounty } = &event.kind {
+        let mut bounty = Bounty::clone(bounty);
+        bounty.created_at = Some(event.timestamp);
+        bounties.insert(event.bounty_id, bounty);
+        return;
+    }
+
+    let bounty = match bounties.get_mut(&event.bounty_id) {
+        Some(bounty) => bounty,
+        None => return,
+    };
+    match &event.kind {
+        // Handled above, a bounty is only created once.
+        EventKind::Created { .. } => (),
+        EventKind::Confirming => bounty.status = BountyStatus::Confirming,
+        EventKind::ConfirmationFailed => bounty.status = BountyStatus::Pending,
+        EventKind::Confirmed { channel_id } => {
+            bounty.channel_id = Some(*channel_id);
+            bounty.status = BountyStatus::Confirmed;
+        }
+        EventKind::Accepted => {
+            bounty.status = BountyStatus::Accepted;
+            bounty.accepted_at = Some(event.timestamp);
+        }
+        EventKind::Declined => bounty.status = BountyStatus::Declined,
+        EventKind::Completed => {
+            bounty.status = BountyStatus::Completed;
+            bounty.completed_at = Some(event.timestamp);
+        }
+        EventKind::Reopened => {
+            bounty.status = BountyStatus::Accepted;
+            bounty.completed_at = None;
+        }
+        EventKind::Cancelled => bounty.status = BountyStatus::Cancelled,
+        EventKind::PayoutUpdated { payout } => bounty.payout = Some(payout.clone()),
+    }
+}
+
+/// The bounties as they were at `at`, or as they are now when `at` is `None`.
+pub fn project(events: &[BountyEvent], at: Option<Timestamp>) -> HashMap<Uuid, Bounty> {
+    let mut bounties = HashMap::new();
+    for event in events
+        .iter()
+        .filter(|event| at.is_none_or(|at| event.timestamp.unix_timestamp() <= at.unix_timestamp()))
+    {
+        apply(&mut bounties, event);
+    }
+    bounties
+}
+
+/// Reconstructs a history for bounties stored before events were recorded, using the
+/// timestamps they kept. A step without a timestamp is dated to the step before it.
+pub fn backfill(bounties: &HashMap<Uuid, Bounty>) -> Vec<BountyEvent> {
+    let mut events = Vec::new();
+    for (id, bounty) in bounties {
+        let mut last = bounty.created_at.unwrap_or_else(Timestamp::now);
+        let mut push = |timestamp: Option<Timestamp>, kind| {
+            if let Some(timestamp) = timestamp {
+                last = timestamp;
+            }
+            events.push(BountyEvent {
+                bounty_id: *id,
+                timestamp: last,
+                kind,
+            });
+        };
+
+        let mut initial = bounty.clone();
+        initial.status = BountyStatus::Pending;
+        initial.channel_id = None;
+        initial.payout = None;
+        initial.accepted_at = None;
+        initial.completed_at = None;
+        push(
+            None,
+            EventKind::Created {
+                bounty: Box::new(initial),
+            },
+        );
+
+        if let Some(channel_id) = bounty.channel_id {
+            push(None, EventKind::Confirmed { channel_id });
+        }
+        if bounty.accepted_at.is_some() || bounty.status == BountyStatus::Accepted {
+            push(bounty.accepted_at, EventKind::Accepted);
+        }
+        match bounty.status {
+            BountyStatus::Declined => push(None, EventKind::Declined),
+            BountyStatus::Completed => push(bounty.completed_at, EventKind::Completed),
+            BountyStatus::Cancelled => push(None, EventKind::Cancelled),
+            _ => (),
+        }
+        if let Some(payout) = &bounty.payout {
+            push(
+                None,
+                EventKind::PayoutUpdated {
+                    payout: payout.clone(),
+                },
+            );
+        }
+    }
+    events.sort_by_key(|event| event.timestamp.unix_timestamp());
+    events
+}
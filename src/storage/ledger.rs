@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+use serenity::model::{id::UserId, Timestamp};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntryKind {
+    /// Reward taken from the lister when the bounty is confirmed.
+    Hold,
+    /// Escrowed reward paid out to the hunter on completion.
+    Release,
+    /// Escrowed reward returned to the lister on cancel or decline.
+    Refund,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub bounty_id: Uuid,
+    pub kind: EntryKind,
+    pub user: UserId,
+    pub amount: u64,
+    pub currency: String,
+    pub timestamp: Timestamp,
+}
+
+/// Every movement of escrowed money. Entries are only ever appended, like bounty events, but
+/// the ledger is kept beside the event history rather than projected from it: an entry records
+/// the amount and recipient at the time of payment, which a later fix to how events apply must
+/// never change, and `escrowed_at` already answers what was held at any point.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ledger {
+    entries: Vec<LedgerEntry>,
+}
+
+impl Ledger {
+    pub fn hold(&mut self, bounty_id: Uuid, lister: UserId, amount: u64, currency: &str) {
+        if amount == 0 || self.escrowed(&bounty_id).is_some() {
+            return;
+        }
+
+        self.push(bounty_id, EntryKind::Hold, lister, amount, currency);
+    }
+
+    /// Pays the escrowed reward of a bounty to the hunter, returning the amount released.
+    pub fn release(&mut self, bounty_id: Uuid, hunter: UserId) -> Option<(u64, String)> {
+        let (amount, currency) = self.escrowed(&bounty_id)?;
+        self.push(bounty_id, EntryKind::Release, hunter, amount, &currency);
+        Some((amount, currency))
+    }
+
+    /// Returns the escrowed reward of a bounty to the lister, returning the amount refunded.
+    pub fn refund(&mut self, bounty_id: Uuid, lister: UserId) -> Option<(u64, String)> {
+        let (amount, currency) = self.escrowed(&bounty_id)?;
+        self.push(bounty_id, EntryKind::Refund, lister, amount, &currency);
+        Some((amount, currency))
+    }
+
+    /// The reward currently held for a bounty, if it has not been released or refunded yet.
+    pub fn escrowed(&self, bounty_id: &Uuid) -> Option<(u64, String)> {
+        self.held(bounty_id, None)
+    }
+
+    /// The reward that was held for a bounty at `at`.
+    pub fn escrowed_at(&self, bounty_id: &Uuid, at: Timestamp) -> Option<(u64, String)> {
+        self.held(bounty_id, Some(at))
+    }
+
+    fn held(&self, bounty_id: &Uuid, at: Option<Timestamp>) -> Option<(u64, String)> {
+        let mut held = None;
+        for entry in self
+            .entries
+            .iter()
+            .filter(|e| e.bounty_id == *bounty_id)
+            .filter(|e| at.is_none_or(|at| e.timestamp.unix_timestamp() <= at.unix_timestamp()))
+        {
+            match entry.kind {
+                EntryKind::Hold => held = Some((entry.amount, entry.currency.clone())),
+                EntryKind::Release | EntryKind::Refund => held = None,
+            }
+        }
+
+        held
+    }
+
+    /// Net balance of a user per currency.
+    pub fn balances(&self, user: UserId) -> BTreeMap<String, i64> {
+        let mut balances = BTreeMap::new();
+        for entry in self.entries.iter().filter(|e| e.user == user) {
+            let balance = balances.entry(entry.currency.clone()).or_insert(0);
+            match entry.kind {
+                EntryKind::Hold => *balance -= entry.amount as i64,
+                EntryKind::Release | EntryKind::Refund => *balance += entry.amount as i64,
+            }
+        }
+
+        balances
+    }
+
+    /// Amount per currency a user currently has locked in escrow.
+    pub fn in_escrow(&self, user: UserId) -> BTreeMap<String, u64> {
+        let mut held = BTreeMap::new();
+        for entry in self
+            .entries
+            .iter()
+            .filter(|e| e.user == user && e.kind == EntryKind::Hold)
+        {
+            if self.escrowed(&entry.bounty_id).is_some() {
+                *held.entry(entry.currency.clone()).or_insert(0) += entry.amount;
+            }
+        }
+
+        held
+    }
+
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    fn push(
+        &mut self,
+        bounty_id: Uuid,
+        kind: EntryKind,
+        user: UserId,
+        amount: u64,
+        currency: &str,
+    ) {
+        self.entries.push(LedgerEntry {
+            bounty_id,
+            kind,
+            user,
+            amount,
+            currency: currency.to_string(),
+            timestamp: Timestamp::now(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTER: UserId = UserId(1);
+    const HUNTER: UserId = UserId(2);
+
+    #[test]
+    fn a_bounty_is_held_once_and_settled_once() {
+        let bounty = Uuid::new_v4();
+        let mut ledger = Ledger::default();
+
+        ledger.hold(bounty, LISTER, 0, "USD");
+        assert!(ledger.escrowed(&bounty).is_none());
+
+        ledger.hold(bounty, LISTER, 25, "USD");
+        ledger.hold(bounty, LISTER, 40, "USD");
+        assert_eq!(ledger.escrowed(&bounty), Some((25, String::from("USD"))));
+
+        assert_eq!(
+            ledger.release(bounty, HUNTER),
+            Some((25, String::from("USD")))
+        );
+        assert!(ledger.escrowed(&bounty).is_none());
+        assert!(ledger.release(bounty, HUNTER).is_none());
+        assert!(ledger.refund(bounty, LISTER).is_none());
+        assert_eq!(ledger.entries().len(), 2);
+    }
+
+    #[test]
+    fn a_refund_returns_the_reward_to_the_lister() {
+        let bounty = Uuid::new_v4();
+        let mut ledger = Ledger::default();
+
+        ledger.hold(bounty, LISTER, 10, "EUR");
+        assert_eq!(
+            ledger.refund(bounty, LISTER),
+            Some((10, String::from("EUR")))
+        );
+        assert!(ledger.escrowed(&bounty).is_none());
+        assert!(ledger
+            .balances(LISTER)
+            .values()
+            .all(|balance| *balance == 0));
+        assert!(ledger.in_escrow(LISTER).is_empty());
+    }
+
+    #[test]
+    fn balances_and_escrow_are_kept_per_currency() {
+        let (paid, held, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut ledger = Ledger::default();
+
+        ledger.hold(paid, LISTER, 25, "USD");
+        ledger.release(paid, HUNTER);
+        ledger.hold(held, LISTER, 10, "USD");
+        ledger.hold(other, LISTER, 5, "EUR");
+
+        assert_eq!(
+            ledger.balances(LISTER),
+            BTreeMap::from([(String::from("EUR"), -5), (String::from("USD"), -35)])
+        );
+        assert_eq!(
+            ledger.balances(HUNTER),
+            BTreeMap::from([(String::from("USD"), 25)])
+        );
+        assert_eq!(
+            ledger.in_escrow(LISTER),
+            BTreeMap::from([(String::from("EUR"), 5), (String::from("USD"), 10)])
+        );
+        assert!(ledger.in_escrow(HUNTER).is_empty());
+    }
+}
@@ -0,0 +1,7 @@
+pub mod achievements;
+pub mod audit;
+pub mod events;
+pub mod ledger;
+pub mod ratings;
+pub mod seasons;
+pub mod store;
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use serenity::model::{id::UserId, Timestamp};
+use std::fmt;
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rating {
+    pub bounty_id: Uuid,
+    pub hunter: UserId,
+    pub rater: UserId,
+    pub score: u8,
+    pub comment: Option<String>,
+    pub timestamp: Timestamp,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Reputation {
+    pub average: f64,
+    pub count: usize,
+}
+
+impl fmt::Display for Reputation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = if self.count == 1 { "" } else { "s" };
+        write!(f, "{:.1}/5 ({} rating{})", self.average, self.count, plural)
+    }
+}
+
+/// Adds a rating, replacing any earlier rating given for the same bounty.
+pub fn rate(ratings: &mut Vec<Rating>, rating: Rating) -> Result<()> {
+    if !(1..=5).contains(&rating.score) {
+        return Err(Error::Validation(String::from(
+            "Ratings must be between 1 and 5",
+        )));
+    }
+
+    ratings.retain(|r| r.bounty_id != rating.bounty_id);
+    ratings.push(rating);
+    Ok(())
+}
+
+/// Aggregated score of all ratings a hunter received, `None` when unrated.
+pub fn reputation(ratings: &[Rating], hunter: UserId) -> Option<Reputation> {
+    let scores: Vec<u8> = ratings
+        .iter()
+        .filter(|r| r.hunter == hunter)
+        .map(|r| r.score)
+        .collect();
+
+    if scores.is_empty() {
+        return None;
+    }
+
+    Some(Reputation {
+        average: scores.iter().map(|&s| s as f64).sum::<f64>() / scores.len() as f64,
+        count: scores.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HUNTER: UserId = UserId(2);
+
+    fn rating(bounty_id: Uuid, hunter: UserId, score: u8) -> Rating {
+        Rating {
+            bounty_id,
+            hunter,
+            rater: UserId(1),
+            score,
+            comment: None,
+            timestamp: Timestamp::now(),
+        }
+    }
+
+    #[test]
+    fn scores_must_be_one_to_five_stars() {
+        let mut ratings = Vec::new();
+        for score in [0, 6] {
+            assert!(matches!(
+                rate(&mut ratings, rating(Uuid::new_v4(), HUNTER, score)),
+                Err(Error::Validation(_))
+            ));
+        }
+        assert!(ratings.is_empty());
+
+        for score in 1..=5 {
+            rate(&mut ratings, rating(Uuid::new_v4(), HUNTER, score)).unwrap();
+        }
+        assert_eq!(ratings.len(), 5);
+    }
+
+    #[test]
+    fn rating_a_bounty_again_replaces_the_old_rating() {
+        let bounty = Uuid::new_v4();
+        let mut ratings = Vec::new();
+        rate(&mut ratings, rating(bounty, HUNTER, 1)).unwrap();
+        rate(&mut ratings, rating(Uuid::new_v4(), HUNTER, 4)).unwrap();
+        rate(&mut ratings, rating(bounty, HUNTER, 5)).unwrap();
+
+        let reputation = reputation(&ratings, HUNTER).unwrap();
+        assert_eq!(reputation.count, 2);
+        assert_eq!(reputation.average, 4.5);
+        assert_eq!(reputation.to_string(), "4.5/5 (2 ratings)");
+    }
+
+    #[test]
+    fn reputation_only_counts_the_hunters_ratings() {
+        let mut ratings = Vec::new();
+        rate(&mut ratings, rating(Uuid::new_v4(), HUNTER, 3)).unwrap();
+        rate(&mut ratings, rating(Uuid::new_v4(), UserId(3), 5)).unwrap();
+
+        assert_eq!(
+            reputation(&ratings, HUNTER).unwrap().to_string(),
+            "3.0/5 (1 rating)"
+        );
+        assert!(reputation(&ratings, UserId(4)).is_none());
+    }
+}
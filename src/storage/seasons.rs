@@ -0,0 +1,74 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serenity::model::{id::UserId, Timestamp};
+use std::{env, fs};
+
+use crate::error::{Error, Result};
+
+static SEASONS: Lazy<Result<Vec<Season>>> = Lazy::new(load_seasons);
+
+/// A competition season as defined in the `SEASONS_FILE` config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Season {
+    pub id: String,
+    pub name: String,
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+impl Season {
+    /// Unix timestamps bounding the season, end exclusive.
+    pub fn window(&self) -> (i64, i64) {
+        (self.start.unix_timestamp(), self.end.unix_timestamp())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Champion {
+    pub user: UserId,
+    pub name: String,
+    pub label: String,
+}
+
+/// Final standings of a closed season, kept for the hall of fame.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonResult {
+    pub season: Season,
+    pub completed: Vec<Champion>,
+    pub earned: Vec<Champion>,
+    pub rating: Vec<Champion>,
+    /// Whether the final standings were posted. Results saved before this was tracked had
+    /// already been announced once.
+    #[serde(default = "announced_before_tracking")]
+    pub announced: bool,
+}
+
+fn announced_before_tracking() -> bool {
+    true
+}
+
+fn load_seasons() -> Result<Vec<Season>> {
+    let path = match env::var("SEASONS_FILE") {
+        Ok(path) => path,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|err| Error::Config(format!("could not read {}: {}", path, err)))?;
+    serde_json::from_str(&contents)
+        .map_err(|err| Error::Config(format!("could not parse {}: {}", path, err)))
+}
+
+/// The seasons defined in `SEASONS_FILE`, none if it is not set.
+pub fn all() -> Result<&'static [Season]> {
+    SEASONS.as_deref().map_err(|err| err.clone())
+}
+
+/// The season running right now, if any.
+pub fn current() -> Result<Option<&'static Season>> {
+    let now = Timestamp::now().unix_timestamp();
+    Ok(all()?.iter().find(|season| {
+        let (start, end) = season.window();
+        start <= now && now < end
+    }))
+}
@@ -0,0 +1,199 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serenity::model::Timestamp;
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, Write},
+    path::Path,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+use uuid::Uuid;
+
+use crate::commands::bounty::Bounty;
+use crate::error::{Error, Result};
+use crate::metrics;
+use crate::storage::achievements::Unlock;
+use crate::storage::audit::AuditEvent;
+use crate::storage::events::{self, BountyEvent};
+use crate::storage::ledger::Ledger;
+use crate::storage::ratings::Rating;
+use crate::storage::seasons::SeasonResult;
+
+const DEFAULT_DATA_FILE: &str = "bounties.json";
+
+/// The store, or why the data file could not be loaded. Until it loads, readers see an empty
+/// store and every write fails, so a data file the bot cannot parse is never overwritten.
+static STORE: Lazy<Mutex<Result<Store>>> = Lazy::new(|| Mutex::new(Store::load()));
+static EMPTY: Lazy<Store> = Lazy::new(Store::default);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Store {
+    /// The current state of every bounty, derived from `events` and kept for readers. It is
+    /// only read from data files saved before the history existed, to backfill it.
+    #[serde(default, skip_serializing)]
+    pub bounties: HashMap<Uuid, Bounty>,
+    #[serde(default)]
+    pub events: Vec<BountyEvent>,
+    pub ledger: Ledger,
+    #[serde(default)]
+    pub ratings: Vec<Rating>,
+    #[serde(default)]
+    pub achievements: Vec<Unlock>,
+    #[serde(default)]
+    pub hall_of_fame: Vec<SeasonResult>,
+    #[serde(default)]
+    pub audit: Vec<AuditEvent>,
+}
+
+impl Store {
+    fn load() -> Result<Store> {
+        let mut store = Store::parse(&data_file())?;
+        if store.events.is_empty() {
+            store.events = events::backfill(&store.bounties);
+        }
+        store.rebuild();
+        Ok(store)
+    }
+
+    /// The store saved in `file`, empty if there is none yet.
+    fn parse(file: &str) -> Result<Store> {
+        match fs::read_to_string(file) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|err| Error::Storage(format!("could not parse {}: {}", file, err))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Store::default()),
+            Err(err) => Err(Error::Storage(format!("could not read {}: {}", file, err))),
+        }
+    }
+
+    /// Records `event` and applies it to the current bounties.
+    pub fn append(&mut self, event: BountyEvent) {
+        events::apply(&mut self.bounties, &event);
+        self.events.push(event);
+    }
+
+    /// Derives the bounties from their history again, picking up fixes to how events apply.
+    pub fn rebuild(&mut self) {
+        self.bounties = events::project(&self.events, None);
+    }
+
+    /// Writes the store to a temporary file next to the data file and renames it into
+    /// place, so a crash or a full disk never leaves a half-written data file behind.
+    fn save(&self) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|err| Error::Storage(format!("could not serialize bounty store: {}", err)))?;
+        let file = data_file();
+        let temp = format!("{}.tmp", file);
+        let saved = fs::File::create(&temp)
+            .and_then(|mut out| {
+                out.write_all(contents.as_bytes())?;
+                out.sync_all()
+            })
+            .and_then(|()| fs::rename(&temp, &file));
+        saved.map_err(|err| {
+            let _ = fs::remove_file(&temp);
+            Error::Storage(format!("could not save bounty store: {}", err))
+        })
+    }
+}
+
+fn data_file() -> String {
+    env::var("DATA_FILE").unwrap_or_else(|_| String::from(DEFAULT_DATA_FILE))
+}
+
+/// Whether the store can still be saved: it must have loaded, and the data file must be
+/// writable or its directory must exist so it can be created. The file is not parsed again.
+pub fn check() -> Result<()> {
+    init()?;
+    let file = data_file();
+    let writable = if Path::new(&file).exists() {
+        fs::OpenOptions::new().append(true).open(&file).map(|_| ())
+    } else {
+        let dir = Path::new(&file)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        match fs::metadata(dir) {
+            Ok(metadata) if metadata.is_dir() && !metadata.permissions().readonly() => Ok(()),
+            Ok(_) => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "directory is not writable",
+            )),
+            Err(err) => Err(err),
+        }
+    };
+    writable.map_err(|err| Error::Storage(format!("data file {} is not writable: {}", file, err)))
+}
+
+/// Loads the data file if that has not happened yet, failing if it cannot be parsed.
+pub fn init() -> Result<()> {
+    match &*lock() {
+        Ok(_) => Ok(()),
+        Err(err) => Err(err.clone()),
+    }
+}
+
+/// A handler that panicked mid-write leaves the store usable, so poisoning is ignored.
+fn lock() -> MutexGuard<'static, Result<Store>> {
+    STORE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Runs `f` against the shared store without persisting anything.
+pub fn read<T>(f: impl FnOnce(&Store) -> T) -> T {
+    match &*lock() {
+        Ok(store) => f(store),
+        Err(_) => f(&EMPTY),
+    }
+}
+
+/// Runs `f` against the shared store and writes the result to disk. If saving fails the
+/// changes `f` made are undone, so memory never holds state the data file does not. The
+/// events `f` appended are only counted once they are saved.
+pub fn write<T>(f: impl FnOnce(&mut Store) -> T) -> Result<T> {
+    let mut guard = lock();
+    let store = guard.as_mut().map_err(|err| err.clone())?;
+    let before = store.clone();
+    let result = f(store);
+    if let Err(err) = store.save() {
+        *store = before;
+        return Err(err);
+    }
+    for event in store.events.get(before.events.len()..).unwrap_or_default() {
+        metrics::bounty_event(&event.kind);
+    }
+    Ok(result)
+}
+
+/// Records `event` and saves the store.
+pub fn append(event: BountyEvent) -> Result<()> {
+    write(|store| store.append(event))
+}
+
+pub fn get_bounty(id: &Uuid) -> Option<Bounty> {
+    read(|store| store.bounties.get(id).cloned())
+}
+
+/// The most recently created bounty with the given bounty number.
+pub fn find_by_number(number: u32) -> Option<(Uuid, Bounty)> {
+    read(|store| {
+        store
+            .bounties
+            .iter()
+            .filter(|(_, bounty)| bounty.bounty_number == number)
+            .max_by_key(|(_, bounty)| bounty.created_at.map(|t| t.unix_timestamp()))
+            .map(|(id, bounty)| (*id, bounty.clone()))
+    })
+}
+
+/// A bounty as it was at `at`, `None` if it did not exist yet.
+pub fn bounty_at(id: &Uuid, at: Timestamp) -> Option<Bounty> {
+    read(|store| {
+        let events: Vec<BountyEvent> = store
+            .events
+            .iter()
+            .filter(|event| event.bounty_id == *id)
+            .cloned()
+            .collect();
+        events::project(&events, Some(at)).remove(id)
+    })
+}
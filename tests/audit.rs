@@ -0,0 +1,40 @@
+//! Mirrors audit events into the staff log channel of an in-memory guild.
+
+use std::env;
+
+use serenity::model::id::{GuildId, UserId};
+use uuid::Uuid;
+
+use discord_bot_rust::commands::audit::record;
+use discord_bot_rust::discord_util::fake::FakeGuild;
+use discord_bot_rust::storage::{
+    audit::{self, AuditAction, AuditEvent},
+    store,
+};
+
+#[tokio::test]
+async fn events_are_persisted_and_mirrored() {
+    let data_file = env::temp_dir().join(format!("audit-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&data_file);
+    env::set_var("DATA_FILE", data_file);
+
+    let api = FakeGuild::new(GuildId(700));
+    let log = api.add_text_channel("staff-log", None);
+    env::set_var("STAFF_LOG_CHANNEL", log.to_string());
+
+    let id = Uuid::new_v4();
+    let event = AuditEvent::new(id, 4, AuditAction::ForceClosed, Some(UserId(70)))
+        .detail("Reason: duplicate");
+    record(&api, vec![event]).await;
+
+    let stored = store::read(|store| audit::timeline(&store.audit, &id).len());
+    assert_eq!(stored, 1);
+
+    let calls = api.take_calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].method, "send_message");
+    assert_eq!(calls[0].target, log.to_string());
+    let embed = &calls[0].body["embeds"][0];
+    assert_eq!(embed["title"], "Bounty #4 force-closed");
+    assert_eq!(embed["description"], "By <@70>\nReason: duplicate");
+}
@@ -0,0 +1,576 @@
+//! Runs bounties through the interaction handlers against an in-memory guild.
+
+use std::{env, sync::Once};
+
+use serde_json::{json, Value};
+use serenity::model::{
+    id::{ChannelId, GuildId, RoleId, UserId},
+    prelude::{interaction::Interaction, PermissionOverwriteType},
+};
+
+use discord_bot_rust::commands::{
+    bounty::{self, BountyStatus},
+    component_id::ComponentId,
+    registry::dispatch,
+};
+use discord_bot_rust::discord_util::fake::{Call, FakeGuild};
+use discord_bot_rust::error::Error;
+use discord_bot_rust::storage::{
+    audit::{self, AuditAction},
+    store,
+};
+
+const GUILD: GuildId = GuildId(900);
+const BOUNTY_CHANNEL: ChannelId = ChannelId(901);
+const LISTER: UserId = UserId(10);
+const HUNTER: UserId = UserId(11);
+const STAFF: UserId = UserId(12);
+const STAFF_ROLE: RoleId = RoleId(950);
+
+static SETUP: Once = Once::new();
+
+fn setup() {
+    SETUP.call_once(|| {
+        let data_file = env::temp_dir().join(format!("bounty-flow-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&data_file);
+        env::set_var("DATA_FILE", data_file);
+        env::set_var("BOUNTY_CATEGORY", "Bounties");
+        env::set_var("ARCHIVE_CATEGORY", "Archive");
+        env::set_var("STAFF_ROLES", STAFF_ROLE.to_string());
+    });
+}
+
+struct Guild {
+    api: FakeGuild,
+    bounties: ChannelId,
+    archive: ChannelId,
+}
+
+fn guild() -> Guild {
+    setup();
+    let api = FakeGuild::new(GUILD);
+    let bounties = api.add_category("Bounties");
+    let archive = api.add_category("Archive");
+    for (id, name) in [(LISTER, "lister"), (HUNTER, "hunter"), (STAFF, "staff")] {
+        api.add_user(serde_json::from_value(user(id, name)).unwrap());
+    }
+    Guild {
+        api,
+        bounties,
+        archive,
+    }
+}
+
+fn user(id: UserId, name: &str) -> Value {
+    json!({
+        "id": id.to_string(),
+        "username": name,
+        "discriminator": "0001",
+        "avatar": null,
+    })
+}
+
+fn member(id: UserId, roles: &[RoleId]) -> Value {
+    let name = match id {
+        LISTER => "lister",
+        HUNTER => "hunter",
+        _ => "staff",
+    };
+    json!({
+        "user": user(id, name),
+        "roles": roles.iter().map(|role| role.to_string()).collect::<Vec<_>>(),
+        "joined_at": "2023-01-01T00:00:00Z",
+        "deaf": false,
+        "mute": false,
+        "permissions": "0",
+    })
+}
+
+/// An interaction as Discord sends it over the gateway.
+fn interaction(kind: u8, id: u64, member: Value, channel: ChannelId, data: Value) -> Value {
+    json!({
+        "id": id.to_string(),
+        "application_id": "1",
+        "type": kind,
+        "token": format!("token-{}", id),
+        "version": 1,
+        "guild_id": GUILD.to_string(),
+        "channel_id": channel.to_string(),
+        "member": member,
+        "locale": "en-US",
+        "guild_locale": "en-US",
+        "data": data,
+    })
+}
+
+fn start_command(id: u64, number: u32, reward: u64) -> Interaction {
+    let data = json!({
+        "id": "50",
+        "name": "bounty",
+        "type": 1,
+        "options": [{
+            "name": "start",
+            "type": 1,
+            "options": [
+                { "name": "hunter", "type": 6, "value": HUNTER.to_string() },
+                { "name": "bounty-number", "type": 4, "value": number },
+                { "name": "reward", "type": 4, "value": reward },
+            ],
+        }],
+        "resolved": {
+            "users": { HUNTER.to_string(): user(HUNTER, "hunter") },
+        },
+    });
+    let command = interaction(2, id, member(LISTER, &[]), BOUNTY_CHANNEL, data);
+    serde_json::from_value(command).unwrap()
+}
+
+fn button(id: u64, member: Value, channel: ChannelId, custom_id: &str) -> Interaction {
+    let message = json!({
+        "id": "60",
+        "channel_id": channel.to_string(),
+        "author": user(UserId(1), "bot"),
+        "content": "",
+        "timestamp": "2023-01-01T00:00:00Z",
+        "edited_timestamp": null,
+        "tts": false,
+        "mention_everyone": false,
+        "mentions": [],
+        "mention_roles": [],
+        "attachments": [],
+        "embeds": [],
+        "pinned": false,
+        "type": 0,
+    });
+    let mut component = interaction(
+        3,
+        id,
+        member,
+        channel,
+        json!({ "custom_id": custom_id, "component_type": 2 }),
+    );
+    component["message"] = message;
+    serde_json::from_value(component).unwrap()
+}
+
+/// The custom ID of the first button in a recorded message body.
+fn first_button(call: &Call) -> String {
+    let body = call.body.get("data").unwrap_or(&call.body);
+    body["components"][0]["components"][0]["custom_id"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{} has no button: {}", call.method, call.body))
+        .to_string()
+}
+
+fn find<'a>(calls: &'a [Call], method: &str) -> &'a Call {
+    calls
+        .iter()
+        .find(|call| call.method == method)
+        .unwrap_or_else(|| panic!("no {} in {:?}", method, calls))
+}
+
+fn bounty_id(custom_id: &str) -> uuid::Uuid {
+    match ComponentId::decode(custom_id).unwrap() {
+        ComponentId::Confirm(id) | ComponentId::Accept(id) | ComponentId::Complete(id) => id,
+        other => panic!("unexpected component {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn bounty_runs_from_start_to_completion() {
+    let guild = guild();
+    let api = &guild.api;
+
+    dispatch(api, start_command(1, 7, 50)).await;
+    let calls = api.take_calls();
+    let confirm_id = first_button(find(&calls, "respond"));
+    let id = bounty_id(&confirm_id);
+    let bounty = store::get_bounty(&id).unwrap();
+    assert_eq!(bounty.status, BountyStatus::Pending);
+    assert_eq!(bounty.hunter.id, HUNTER);
+
+    dispatch(
+        api,
+        button(2, member(LISTER, &[]), BOUNTY_CHANNEL, &confirm_id),
+    )
+    .await;
+    let calls = api.take_calls();
+    let bounty = store::get_bounty(&id).unwrap();
+    assert_eq!(bounty.status, BountyStatus::Confirmed);
+    let channel_id = bounty.channel_id.unwrap();
+    let channel = api.channel(channel_id).unwrap();
+    assert_eq!(channel.parent_id, Some(guild.bounties));
+    assert!(channel
+        .overwrites
+        .iter()
+        .any(|o| o.kind == PermissionOverwriteType::Member(HUNTER)));
+    assert_eq!(
+        store::read(|store| store.ledger.escrowed(&id)),
+        Some((50, String::from("USD")))
+    );
+    let accept_id = first_button(find(&calls, "send_message"));
+    assert_eq!(find(&calls, "send_message").target, channel_id.to_string());
+
+    dispatch(api, button(3, member(HUNTER, &[]), channel_id, &accept_id)).await;
+    let calls = api.take_calls();
+    assert_eq!(
+        store::get_bounty(&id).unwrap().status,
+        BountyStatus::Accepted
+    );
+    assert!(api
+        .channel(channel_id)
+        .unwrap()
+        .overwrites
+        .iter()
+        .any(|o| o.kind == PermissionOverwriteType::Role(STAFF_ROLE)));
+    let complete_id = first_button(find(&calls, "follow_up"));
+
+    dispatch(
+        api,
+        button(4, member(STAFF, &[STAFF_ROLE]), channel_id, &complete_id),
+    )
+    .await;
+    let calls = api.take_calls();
+    let bounty = store::get_bounty(&id).unwrap();
+    assert_eq!(bounty.status, BountyStatus::Completed);
+    assert!(bounty.completed_at.is_some());
+    assert_eq!(store::read(|store| store.ledger.escrowed(&id)), None);
+    assert_eq!(
+        api.channel(channel_id).unwrap().parent_id,
+        Some(guild.archive)
+    );
+    assert_eq!(find(&calls, "edit_response").body["content"], "Completed");
+    assert_eq!(find(&calls, "direct_message").target, LISTER.to_string());
+
+    let actions: Vec<(AuditAction, Option<UserId>)> = store::read(|store| {
+        audit::timeline(&store.audit, &id)
+            .into_iter()
+            .map(|event| (event.action, event.actor))
+            .collect()
+    });
+    assert_eq!(
+        actions,
+        vec![
+            (AuditAction::Started, Some(LISTER)),
+            (AuditAction::ChannelCreated, None),
+            (AuditAction::Confirmed, Some(LISTER)),
+            (AuditAction::Accepted, Some(HUNTER)),
+            (AuditAction::StaffAdded, None),
+            (AuditAction::ChannelMoved, None),
+            (AuditAction::ChannelLocked, None),
+            (AuditAction::Completed, Some(STAFF)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn only_the_lister_can_confirm() {
+    let guild = guild();
+    let api = &guild.api;
+
+    dispatch(api, start_command(11, 8, 0)).await;
+    let confirm_id = first_button(find(&api.take_calls(), "respond"));
+    let id = bounty_id(&confirm_id);
+
+    dispatch(
+        api,
+        button(12, member(HUNTER, &[]), BOUNTY_CHANNEL, &confirm_id),
+    )
+    .await;
+    let calls = api.take_calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(
+        calls[0].body["data"]["content"],
+        "Only the bounty lister can confirm the bounty"
+    );
+    assert_eq!(
+        store::get_bounty(&id).unwrap().status,
+        BountyStatus::Pending
+    );
+    assert!(api.channel_by_name("lister-hunter-bounty8").is_none());
+}
+
+#[tokio::test]
+async fn confirming_twice_creates_one_channel() {
+    let guild = guild();
+    let api = &guild.api;
+
+    dispatch(api, start_command(61, 12, 30)).await;
+    let confirm_id = first_button(find(&api.take_calls(), "respond"));
+    let id = bounty_id(&confirm_id);
+    for interaction in [62, 63] {
+        dispatch(
+            api,
+            button(
+                interaction,
+                member(LISTER, &[]),
+                BOUNTY_CHANNEL,
+                &confirm_id,
+            ),
+        )
+        .await;
+    }
+
+    let calls = api.take_calls();
+    assert_eq!(
+        calls
+            .iter()
+            .filter(|call| call.method == "create_channel")
+            .count(),
+        1
+    );
+    assert_eq!(
+        calls.last().unwrap().body["data"]["content"],
+        "Only pending bounties can be confirmed"
+    );
+    assert_eq!(
+        store::read(|store| store.ledger.escrowed(&id)),
+        Some((30, String::from("USD")))
+    );
+}
+
+#[tokio::test]
+async fn a_failed_confirmation_can_be_retried() {
+    setup();
+    let api = FakeGuild::new(GUILD);
+    api.add_user(serde_json::from_value(user(HUNTER, "hunter")).unwrap());
+
+    dispatch(&api, start_command(71, 13, 25)).await;
+    let confirm_id = first_button(find(&api.take_calls(), "respond"));
+    let id = bounty_id(&confirm_id);
+
+    // Without the bounty category the channel cannot be created.
+    dispatch(
+        &api,
+        button(72, member(LISTER, &[]), BOUNTY_CHANNEL, &confirm_id),
+    )
+    .await;
+    api.take_calls();
+    assert_eq!(
+        store::get_bounty(&id).unwrap().status,
+        BountyStatus::Pending
+    );
+    assert_eq!(store::read(|store| store.ledger.escrowed(&id)), None);
+
+    api.add_category("Bounties");
+    dispatch(
+        &api,
+        button(73, member(LISTER, &[]), BOUNTY_CHANNEL, &confirm_id),
+    )
+    .await;
+    assert_eq!(
+        store::get_bounty(&id).unwrap().status,
+        BountyStatus::Confirmed
+    );
+    assert_eq!(
+        store::read(|store| store.ledger.escrowed(&id)),
+        Some((25, String::from("USD")))
+    );
+}
+
+#[tokio::test]
+async fn only_staff_can_complete() {
+    let guild = guild();
+    let api = &guild.api;
+
+    dispatch(api, start_command(21, 9, 0)).await;
+    let confirm_id = first_button(find(&api.take_calls(), "respond"));
+    let id = bounty_id(&confirm_id);
+    dispatch(
+        api,
+        button(22, member(LISTER, &[]), BOUNTY_CHANNEL, &confirm_id),
+    )
+    .await;
+    api.take_calls();
+
+    let complete_id = ComponentId::Complete(id).encode().unwrap();
+    let channel_id = store::get_bounty(&id).unwrap().channel_id.unwrap();
+    dispatch(
+        api,
+        button(23, member(HUNTER, &[]), channel_id, &complete_id),
+    )
+    .await;
+    let calls = api.take_calls();
+    assert_eq!(
+        calls[0].body["data"]["content"],
+        "Only staff can complete a bounty"
+    );
+    assert_eq!(
+        api.channel(channel_id).unwrap().parent_id,
+        Some(guild.bounties)
+    );
+}
+
+#[tokio::test]
+async fn only_accepted_bounties_can_be_completed() {
+    let guild = guild();
+    let api = &guild.api;
+
+    dispatch(api, start_command(51, 11, 20)).await;
+    let confirm_id = first_button(find(&api.take_calls(), "respond"));
+    let id = bounty_id(&confirm_id);
+    dispatch(
+        api,
+        button(52, member(LISTER, &[]), BOUNTY_CHANNEL, &confirm_id),
+    )
+    .await;
+    api.take_calls();
+
+    let complete_id = ComponentId::Complete(id).encode().unwrap();
+    let channel_id = store::get_bounty(&id).unwrap().channel_id.unwrap();
+    dispatch(
+        api,
+        button(53, member(STAFF, &[STAFF_ROLE]), channel_id, &complete_id),
+    )
+    .await;
+    assert_eq!(
+        api.take_calls()[0].body["data"]["content"],
+        "Only accepted bounties can be completed"
+    );
+    assert_eq!(
+        store::get_bounty(&id).unwrap().status,
+        BountyStatus::Confirmed
+    );
+    assert_eq!(
+        api.channel(channel_id).unwrap().parent_id,
+        Some(guild.bounties)
+    );
+    assert_eq!(
+        store::read(|store| store.ledger.escrowed(&id)),
+        Some((20, String::from("USD")))
+    );
+}
+
+#[tokio::test]
+async fn stale_buttons_are_reported_as_expired() {
+    let guild = guild();
+    let api = &guild.api;
+
+    dispatch(
+        api,
+        button(31, member(LISTER, &[]), BOUNTY_CHANNEL, "v9:confirm:nope"),
+    )
+    .await;
+    let calls = api.take_calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(
+        calls[0].body["data"]["content"],
+        "This button has expired, please run the command again"
+    );
+}
+
+#[tokio::test]
+async fn only_the_hunter_can_decline() {
+    let guild = guild();
+    let api = &guild.api;
+
+    dispatch(api, start_command(41, 10, 15)).await;
+    let confirm_id = first_button(find(&api.take_calls(), "respond"));
+    dispatch(
+        api,
+        button(42, member(LISTER, &[]), BOUNTY_CHANNEL, &confirm_id),
+    )
+    .await;
+    let decline_id = find(&api.take_calls(), "send_message").body["components"][0]["components"][1]
+        ["custom_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let id = bounty_id(&confirm_id);
+    let channel_id = store::get_bounty(&id).unwrap().channel_id.unwrap();
+
+    dispatch(
+        api,
+        button(43, member(LISTER, &[]), channel_id, &decline_id),
+    )
+    .await;
+    assert_eq!(
+        api.take_calls()[0].body["data"]["content"],
+        "Only the bounty hunter can decline the bounty"
+    );
+    assert_eq!(
+        store::get_bounty(&id).unwrap().status,
+        BountyStatus::Confirmed
+    );
+
+    dispatch(
+        api,
+        button(44, member(HUNTER, &[]), channel_id, &decline_id),
+    )
+    .await;
+    assert_eq!(
+        store::get_bounty(&id).unwrap().status,
+        BountyStatus::Declined
+    );
+    assert_eq!(store::read(|store| store.ledger.escrowed(&id)), None);
+    api.take_calls();
+
+    let accept_id = ComponentId::Accept(id).encode().unwrap();
+    dispatch(api, button(45, member(HUNTER, &[]), channel_id, &accept_id)).await;
+    assert_eq!(
+        api.take_calls()[0].body["data"]["content"],
+        "Only confirmed bounties can be accepted"
+    );
+    assert_eq!(
+        store::get_bounty(&id).unwrap().status,
+        BountyStatus::Declined
+    );
+}
+
+fn audit_command(id: u64, roles: &[RoleId], number: u32) -> Interaction {
+    let data = json!({
+        "id": "50",
+        "name": "bounty",
+        "type": 1,
+        "options": [{
+            "name": "audit",
+            "type": 1,
+            "options": [{ "name": "bounty", "type": 3, "value": number.to_string() }],
+        }],
+    });
+    let command = interaction(2, id, member(STAFF, roles), BOUNTY_CHANNEL, data);
+    serde_json::from_value(command).unwrap()
+}
+
+#[tokio::test]
+async fn staff_can_audit_a_bounty() {
+    let guild = guild();
+    let api = &guild.api;
+
+    dispatch(api, start_command(51, 11, 5)).await;
+    let confirm_id = first_button(find(&api.take_calls(), "respond"));
+    dispatch(
+        api,
+        button(52, member(LISTER, &[]), BOUNTY_CHANNEL, &confirm_id),
+    )
+    .await;
+    api.take_calls();
+
+    dispatch(api, audit_command(53, &[], 11)).await;
+    assert_eq!(
+        api.take_calls()[0].body["data"]["content"],
+        "Only staff can audit bounties"
+    );
+
+    dispatch(api, audit_command(54, &[STAFF_ROLE], 11)).await;
+    let calls = api.take_calls();
+    let embed = &calls[0].body["data"]["embeds"][0];
+    assert_eq!(embed["title"], "Audit of bounty #11");
+    let description = embed["description"].as_str().unwrap();
+    let started = description.find("**started**").unwrap();
+    let confirmed = description.find("**confirmed**").unwrap();
+    assert!(started < confirmed);
+    assert!(description.contains(&format!("By <@{}>", LISTER)));
+}
+
+#[test]
+fn currencies_are_short_codes() {
+    assert_eq!(bounty::parse_currency(" eur ").unwrap(), "EUR");
+    assert_eq!(bounty::parse_currency("usdc2").unwrap(), "USDC2");
+    for invalid in ["", "   ", "US D", "€", "ABCDEFGHIJK"] {
+        assert!(matches!(
+            bounty::parse_currency(invalid),
+            Err(Error::Validation(_))
+        ));
+    }
+}
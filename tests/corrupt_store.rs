@@ -0,0 +1,22 @@
+//! Starts against a data file that cannot be parsed.
+
+use std::{env, fs};
+
+use discord_bot_rust::{error::Error, storage::store};
+
+#[test]
+fn a_corrupt_data_file_is_reported_and_never_overwritten() {
+    let data_file = env::temp_dir().join(format!("corrupt-store-{}.json", std::process::id()));
+    fs::write(&data_file, "{ not json").unwrap();
+    env::set_var("DATA_FILE", &data_file);
+
+    assert!(matches!(store::init(), Err(Error::Storage(_))));
+    assert!(matches!(
+        store::write(|store| store.ratings.clear()),
+        Err(Error::Storage(_))
+    ));
+    assert_eq!(store::read(|store| store.bounties.len()), 0);
+    assert_eq!(fs::read_to_string(&data_file).unwrap(), "{ not json");
+
+    let _ = fs::remove_file(&data_file);
+}
@@ -0,0 +1,110 @@
+//! Derives bounties from their event history.
+
+use std::collections::HashMap;
+
+use serde_json::json;
+use serenity::model::{id::ChannelId, user::User, Timestamp};
+use uuid::Uuid;
+
+use discord_bot_rust::commands::bounty::{Bounty, BountyStatus};
+use discord_bot_rust::storage::events::{backfill, project, BountyEvent, EventKind};
+
+fn user(id: u64, name: &str) -> User {
+    serde_json::from_value(json!({
+        "id": id.to_string(),
+        "username": name,
+        "discriminator": "0001",
+        "avatar": null,
+    }))
+    .unwrap()
+}
+
+fn at(secs: i64) -> Timestamp {
+    Timestamp::from_unix_timestamp(secs).unwrap()
+}
+
+fn event(id: Uuid, secs: i64, kind: EventKind) -> BountyEvent {
+    BountyEvent {
+        bounty_id: id,
+        timestamp: at(secs),
+        kind,
+    }
+}
+
+#[test]
+fn state_at_a_point_in_time() {
+    let id = Uuid::new_v4();
+    let bounty = Bounty::new(
+        user(1, "lister"),
+        user(2, "hunter"),
+        5,
+        0,
+        String::from("USD"),
+    );
+    let events = vec![
+        event(
+            id,
+            100,
+            EventKind::Created {
+                bounty: Box::new(bounty),
+            },
+        ),
+        event(
+            id,
+            200,
+            EventKind::Confirmed {
+                channel_id: ChannelId(30),
+            },
+        ),
+        event(id, 300, EventKind::Accepted),
+        event(id, 400, EventKind::Completed),
+        event(id, 500, EventKind::Reopened),
+    ];
+
+    assert!(project(&events, Some(at(50))).is_empty());
+
+    let confirmed = &project(&events, Some(at(250)))[&id];
+    assert_eq!(confirmed.status, BountyStatus::Confirmed);
+    assert_eq!(confirmed.channel_id, Some(ChannelId(30)));
+    assert_eq!(confirmed.created_at, Some(at(100)));
+    assert_eq!(confirmed.accepted_at, None);
+
+    let completed = &project(&events, Some(at(400)))[&id];
+    assert_eq!(completed.status, BountyStatus::Completed);
+    assert_eq!(completed.accepted_at, Some(at(300)));
+    assert_eq!(completed.completed_at, Some(at(400)));
+
+    let now = &project(&events, None)[&id];
+    assert_eq!(now.status, BountyStatus::Accepted);
+    assert_eq!(now.completed_at, None);
+}
+
+#[test]
+fn stored_bounties_are_backfilled() {
+    let id = Uuid::new_v4();
+    let mut bounty = Bounty::new(
+        user(1, "lister"),
+        user(2, "hunter"),
+        6,
+        10,
+        String::from("EUR"),
+    );
+    bounty.created_at = Some(at(100));
+    bounty.accepted_at = Some(at(300));
+    bounty.completed_at = Some(at(400));
+    bounty.channel_id = Some(ChannelId(31));
+    bounty.status = BountyStatus::Completed;
+    let bounties = HashMap::from([(id, bounty)]);
+
+    let events = backfill(&bounties);
+    assert_eq!(events.len(), 4);
+
+    let rebuilt = &project(&events, None)[&id];
+    assert_eq!(rebuilt.status, BountyStatus::Completed);
+    assert_eq!(rebuilt.channel_id, Some(ChannelId(31)));
+    assert_eq!(rebuilt.accepted_at, Some(at(300)));
+    assert_eq!(rebuilt.completed_at, Some(at(400)));
+
+    let accepted = &project(&events, Some(at(350)))[&id];
+    assert_eq!(accepted.status, BountyStatus::Accepted);
+}
@@ -0,0 +1,149 @@
+//! Keeps the guild cache current from the gateway events the bot receives.
+
+use serenity::model::{
+    channel::ChannelType,
+    id::{ChannelId, GuildId, RoleId},
+};
+
+use discord_bot_rust::discord_util::{
+    api::{Channel, Role},
+    guild_cache,
+};
+
+fn text_channel(id: u64, name: &str) -> Channel {
+    Channel {
+        id: ChannelId(id),
+        name: name.to_string(),
+        kind: ChannelType::Text,
+        parent_id: None,
+        overwrites: Vec::new(),
+    }
+}
+
+fn category(id: u64, name: &str) -> Channel {
+    Channel {
+        kind: ChannelType::Category,
+        ..text_channel(id, name)
+    }
+}
+
+fn channel_names(guild: GuildId) -> Option<Vec<String>> {
+    let mut names: Vec<String> = guild_cache::channels(guild)?
+        .into_iter()
+        .map(|channel| channel.name)
+        .collect();
+    names.sort();
+    Some(names)
+}
+
+fn role_names(guild: GuildId) -> Option<Vec<String>> {
+    let mut names: Vec<String> = guild_cache::roles(guild)?
+        .into_iter()
+        .map(|role| role.name)
+        .collect();
+    names.sort();
+    Some(names)
+}
+
+fn role(id: u64, name: &str) -> Role {
+    Role {
+        id: RoleId(id),
+        name: name.to_string(),
+    }
+}
+
+#[test]
+fn channel_events_update_a_loaded_guild() {
+    let guild = GuildId(1);
+    guild_cache::set_channels(guild, vec![text_channel(10, "general")]);
+    assert_eq!(channel_names(guild), Some(vec![String::from("general")]));
+
+    // Created
+    guild_cache::update_channel(guild, category(11, "Bounties"));
+    assert_eq!(
+        guild_cache::category_by_name(guild, "Bounties").map(|c| c.id),
+        Some(ChannelId(11))
+    );
+
+    // Updated
+    guild_cache::update_channel(guild, category(11, "Archive"));
+    assert!(guild_cache::category_by_name(guild, "Bounties").is_none());
+    assert_eq!(
+        guild_cache::category_by_name(guild, "Archive").map(|c| c.id),
+        Some(ChannelId(11))
+    );
+
+    // Deleted
+    guild_cache::remove_channel(guild, ChannelId(11));
+    assert!(guild_cache::category_by_name(guild, "Archive").is_none());
+    assert_eq!(channel_names(guild), Some(vec![String::from("general")]));
+}
+
+#[test]
+fn only_categories_are_found_by_name() {
+    let guild = GuildId(6);
+    guild_cache::set_channels(guild, vec![text_channel(60, "Bounties")]);
+    assert!(guild_cache::category_by_name(guild, "Bounties").is_none());
+
+    guild_cache::update_channel(guild, category(61, "Bounties"));
+    assert_eq!(
+        guild_cache::category_by_name(guild, "Bounties").map(|c| c.id),
+        Some(ChannelId(61))
+    );
+}
+
+#[test]
+fn events_for_guilds_not_loaded_are_skipped() {
+    let guild = GuildId(2);
+    guild_cache::update_channel(guild, category(20, "Bounties"));
+    guild_cache::update_role(guild, role(21, "Staff"));
+
+    assert!(guild_cache::channels(guild).is_none());
+    assert!(guild_cache::roles(guild).is_none());
+    assert!(guild_cache::category_by_name(guild, "Bounties").is_none());
+}
+
+#[test]
+fn changes_the_bot_made_invalidate_the_guild_channels() {
+    let guild = GuildId(3);
+    guild_cache::set_channels(
+        guild,
+        vec![category(30, "Bounties"), text_channel(31, "bounty")],
+    );
+
+    guild_cache::invalidate_channel(ChannelId(31));
+    assert!(guild_cache::channels(guild).is_none());
+    assert!(guild_cache::category_by_name(guild, "Bounties").is_none());
+}
+
+#[test]
+fn role_events_update_a_loaded_guild() {
+    let guild = GuildId(4);
+    guild_cache::set_roles(guild, vec![role(40, "@everyone")]);
+
+    guild_cache::update_role(guild, role(41, "Staff"));
+    assert_eq!(
+        role_names(guild),
+        Some(vec![String::from("@everyone"), String::from("Staff")])
+    );
+
+    guild_cache::update_role(guild, role(41, "Moderators"));
+    assert_eq!(
+        role_names(guild),
+        Some(vec![String::from("@everyone"), String::from("Moderators")])
+    );
+
+    guild_cache::remove_role(guild, RoleId(41));
+    assert_eq!(role_names(guild), Some(vec![String::from("@everyone")]));
+}
+
+#[test]
+fn removed_guilds_are_forgotten() {
+    let guild = GuildId(5);
+    guild_cache::set_channels(guild, vec![text_channel(50, "general")]);
+    guild_cache::set_roles(guild, vec![role(51, "Staff")]);
+
+    guild_cache::remove_guild(guild);
+    assert!(guild_cache::channels(guild).is_none());
+    assert!(guild_cache::roles(guild).is_none());
+}
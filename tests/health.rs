@@ -0,0 +1,69 @@
+//! Probes the health endpoints as the gateway connects and drops.
+
+use std::{env, net::TcpListener, time::Duration};
+
+use serde_json::Value;
+use serenity::gateway::ConnectionStage;
+
+use discord_bot_rust::health;
+use discord_bot_rust::http::{serve, Endpoint};
+
+async fn get(client: &reqwest::Client, url: &str) -> reqwest::Response {
+    for _ in 0..50 {
+        if let Ok(response) = client.get(url).send().await {
+            return response;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("health server did not start");
+}
+
+#[tokio::test]
+async fn readiness_follows_the_gateway_and_storage() {
+    let data_file = env::temp_dir().join(format!("health-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&data_file);
+    env::set_var("DATA_FILE", &data_file);
+
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    tokio::spawn(serve(address, vec![Endpoint::Health]));
+    let client = reqwest::Client::new();
+    let ready = format!("http://{}/readyz", address);
+
+    let live = get(&client, &format!("http://{}/healthz", address)).await;
+    assert_eq!(live.status(), 200);
+
+    let response = get(&client, &ready).await;
+    assert_eq!(response.status(), 503);
+    let report: Value = response.json().await.unwrap();
+    assert_eq!(report["gateway"], "not started");
+
+    health::ready();
+    health::heartbeat(Some(Duration::from_millis(42)));
+    let response = get(&client, &ready).await;
+    assert_eq!(response.status(), 200);
+    let report: Value = response.json().await.unwrap();
+    assert_eq!(report["gateway"], "connected");
+    assert_eq!(report["storage"], "ok");
+    assert_eq!(report["latency_ms"], 42);
+    assert_eq!(report["seconds_since_heartbeat"], 0);
+    assert_eq!(report["seconds_since_ready"], 0);
+
+    health::stage_changed(ConnectionStage::Disconnected);
+    let response = get(&client, &ready).await;
+    assert_eq!(response.status(), 503);
+    let report: Value = response.json().await.unwrap();
+    assert_eq!(report["problems"][0], "The gateway is disconnected");
+
+    health::stage_changed(ConnectionStage::Connected);
+    env::set_var("DATA_FILE", "/nonexistent/bounties.json");
+    let response = get(&client, &ready).await;
+    assert_eq!(response.status(), 503);
+    let report: Value = response.json().await.unwrap();
+    assert_eq!(report["storage"], "unavailable");
+
+    let metrics = get(&client, &format!("http://{}/metrics", address)).await;
+    assert_eq!(metrics.status(), 404);
+}
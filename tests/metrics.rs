@@ -0,0 +1,87 @@
+//! Scrapes the metrics endpoint after some bounty activity.
+
+use std::{env, net::TcpListener, time::Duration};
+
+use serde_json::json;
+use uuid::Uuid;
+
+use discord_bot_rust::commands::bounty::Bounty;
+use discord_bot_rust::http::{serve, Endpoint};
+use discord_bot_rust::metrics;
+use discord_bot_rust::storage::{
+    events::{BountyEvent, EventKind},
+    store,
+};
+
+#[tokio::test]
+async fn metrics_are_served() {
+    let data_file = env::temp_dir().join(format!("metrics-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&data_file);
+    env::set_var("DATA_FILE", data_file);
+    env::set_var("GUILD_ID", "600");
+
+    let user = |id: u64, name: &str| {
+        serde_json::from_value(json!({
+            "id": id.to_string(),
+            "username": name,
+            "discriminator": "0001",
+            "avatar": null,
+        }))
+        .unwrap()
+    };
+    let bounty = Bounty::new(
+        user(1, "lister"),
+        user(2, "hunter"),
+        1,
+        0,
+        String::from("USD"),
+    );
+    store::append(BountyEvent::new(
+        Uuid::new_v4(),
+        EventKind::Created {
+            bounty: Box::new(bounty),
+        },
+    ))
+    .unwrap();
+    metrics::discord_error("create_channel");
+    metrics::interaction_handled("command", "bounty start", Duration::from_millis(120));
+
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    tokio::spawn(serve(address, vec![Endpoint::Metrics]));
+
+    let client = reqwest::Client::new();
+    let mut response = None;
+    for _ in 0..50 {
+        match client
+            .get(format!("http://{}/metrics", address))
+            .send()
+            .await
+        {
+            Ok(sent) => {
+                response = Some(sent);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+    let response = response.expect("metrics server did not start");
+    assert_eq!(response.status(), 200);
+    let body = response.text().await.unwrap();
+
+    assert!(body.contains(r#"bounty_events_total{event="created"} 1"#));
+    assert!(body.contains(r#"discord_api_errors_total{route="create_channel"} 1"#));
+    assert!(body.contains(
+        r#"interaction_duration_seconds_count{command="bounty start",kind="command"} 1"#
+    ));
+    assert!(body.contains(r#"open_bounties{guild="600",status="pending"} 1"#));
+
+    let missing = client
+        .get(format!("http://{}/health", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), 404);
+}
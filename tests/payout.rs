@@ -0,0 +1,139 @@
+//! Pays hunters through the payout providers, against a local webhook endpoint and a
+//! temporary CSV file.
+
+use std::{convert::Infallible, env, fs, net::SocketAddr, sync::Arc};
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Response, Server, StatusCode,
+};
+use serde_json::{json, Value};
+use serenity::model::{id::UserId, user::User};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use discord_bot_rust::commands::bounty::Bounty;
+use discord_bot_rust::error::Error;
+use discord_bot_rust::payout::{
+    csv::CsvProvider, provider::PayoutProvider, webhook::WebhookProvider,
+};
+
+fn user(id: u64, name: &str) -> User {
+    let mut user = User::default();
+    user.id = UserId(id);
+    user.name = name.to_string();
+    user
+}
+
+fn bounty() -> Bounty {
+    Bounty::new(
+        user(1, "lister"),
+        user(2, "Hunter, \"The\" Second"),
+        7,
+        25,
+        String::from("USD"),
+    )
+}
+
+/// Serves every request with `status` and `body`, keeping the request bodies it received.
+fn serve(status: StatusCode, body: Value) -> (SocketAddr, Arc<Mutex<Vec<Value>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let requests = received.clone();
+    let service = make_service_fn(move |_| {
+        let requests = requests.clone();
+        let body = body.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let requests = requests.clone();
+                let body = body.clone();
+                async move {
+                    let request = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                    requests
+                        .lock()
+                        .await
+                        .push(serde_json::from_slice(&request).unwrap());
+                    let mut response = Response::new(Body::from(body.to_string()));
+                    *response.status_mut() = status;
+                    Ok::<_, Infallible>(response)
+                }
+            }))
+        }
+    });
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(service);
+    let address = server.local_addr();
+    tokio::spawn(server);
+    (address, received)
+}
+
+#[tokio::test]
+async fn the_webhook_posts_the_payout_and_returns_its_reference() {
+    let (address, received) = serve(StatusCode::OK, json!({ "reference": "tx-42" }));
+    let provider = WebhookProvider::new(format!("http://{}/payouts", address)).unwrap();
+    let bounty = bounty();
+    let bounty_id = Uuid::new_v4();
+
+    let reference = provider
+        .pay(bounty_id, &bounty, &bounty.hunter, 25, "USD")
+        .await
+        .unwrap();
+
+    assert_eq!(reference, "tx-42");
+    assert_eq!(
+        *received.lock().await,
+        vec![json!({
+            "bounty_id": bounty_id,
+            "bounty_number": 7,
+            "hunter_id": "2",
+            "hunter_name": "Hunter, \"The\" Second",
+            "amount": 25,
+            "currency": "USD",
+        })]
+    );
+}
+
+#[tokio::test]
+async fn a_failing_webhook_fails_the_payout() {
+    let (address, _) = serve(StatusCode::BAD_GATEWAY, json!({}));
+    let provider = WebhookProvider::new(format!("http://{}/payouts", address)).unwrap();
+    let bounty = bounty();
+
+    let result = provider
+        .pay(Uuid::new_v4(), &bounty, &bounty.hunter, 25, "USD")
+        .await;
+
+    assert!(matches!(result, Err(Error::Payout(message)) if message.contains("502")));
+}
+
+#[tokio::test]
+async fn csv_payouts_are_appended_with_quoted_fields() {
+    let path = env::temp_dir().join(format!("payouts-{}.csv", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let provider = CsvProvider::new(path.to_string_lossy().into_owned());
+    let bounty = bounty();
+    let bounty_id = Uuid::new_v4();
+
+    let first = provider
+        .pay(bounty_id, &bounty, &bounty.hunter, 25, "USD")
+        .await
+        .unwrap();
+    let second = provider
+        .pay(bounty_id, &bounty, &bounty.hunter, 5, "EUR")
+        .await
+        .unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("timestamp,reference,"));
+    for (line, reference, amount, currency) in
+        [(lines[1], first, 25, "USD"), (lines[2], second, 5, "EUR")]
+    {
+        assert!(line.ends_with(&format!(
+            ",\"{}\",\"{}\",\"7\",\"2\",\"Hunter, \"\"The\"\" Second\",\"{}\",\"{}\"",
+            reference, bounty_id, amount, currency
+        )));
+    }
+
+    let _ = fs::remove_file(&path);
+}
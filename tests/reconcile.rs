@@ -0,0 +1,209 @@
+//! Reconciles the bounty store with an in-memory guild that drifted while the bot was down.
+
+use std::env;
+
+use serde_json::json;
+use serenity::builder::CreateMessage;
+use serenity::model::{
+    id::{ChannelId, GuildId, RoleId},
+    prelude::{PermissionOverwrite, PermissionOverwriteType, Permissions, User},
+};
+use uuid::Uuid;
+
+use discord_bot_rust::commands::bounty::{Bounty, BountyStatus};
+use discord_bot_rust::discord_util::{
+    api::DiscordApi,
+    channel::{channel_name, expected_permissions},
+    fake::FakeGuild,
+    reconcile::{publish, reconcile},
+};
+use discord_bot_rust::storage::{
+    events::{BountyEvent, EventKind},
+    store,
+};
+
+const GUILD: GuildId = GuildId(800);
+const STAFF_ROLE: RoleId = RoleId(850);
+
+fn user(id: u64, name: &str) -> User {
+    serde_json::from_value(json!({
+        "id": id.to_string(),
+        "username": name,
+        "discriminator": "0001",
+        "avatar": null,
+    }))
+    .unwrap()
+}
+
+/// Stores `bounty` as created and then changed by `history`.
+fn insert(bounty: Bounty, history: Vec<EventKind>) -> Uuid {
+    let id = Uuid::new_v4();
+    store::append(BountyEvent::new(
+        id,
+        EventKind::Created {
+            bounty: Box::new(bounty),
+        },
+    ))
+    .unwrap();
+    for kind in history {
+        store::append(BountyEvent::new(id, kind)).unwrap();
+    }
+    id
+}
+
+#[tokio::test]
+async fn drifted_guild_is_reconciled() {
+    let data_file = env::temp_dir().join(format!("reconcile-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&data_file);
+    env::set_var("DATA_FILE", data_file);
+    env::set_var("BOUNTY_CATEGORY", "Bounties");
+    env::set_var("ARCHIVE_CATEGORY", "Archive");
+    env::set_var("STAFF_ROLES", STAFF_ROLE.to_string());
+
+    let api = FakeGuild::new(GUILD);
+    let bounties = api.add_category("Bounties");
+    let archive = api.add_category("Archive");
+    let log = api.add_text_channel("staff-log", None);
+    env::set_var("STAFF_LOG_CHANNEL", log.to_string());
+    let lister = user(20, "Lister");
+    let hunter = user(21, "hunter");
+
+    // Accepted, but staff lost access to the channel.
+    let accepted = Bounty::new(lister.clone(), hunter.clone(), 1, 10, String::from("USD"));
+    let accepted_channel = api.add_text_channel(&channel_name(&accepted), Some(bounties));
+    let accepted_id = insert(
+        accepted,
+        vec![
+            EventKind::Confirmed {
+                channel_id: accepted_channel,
+            },
+            EventKind::Accepted,
+        ],
+    );
+    let accepted = store::get_bounty(&accepted_id).unwrap();
+
+    // Completed, and its archived channel was deleted.
+    let completed = Bounty::new(lister.clone(), hunter.clone(), 2, 10, String::from("USD"));
+    insert(
+        completed,
+        vec![
+            EventKind::Confirmed {
+                channel_id: ChannelId(42),
+            },
+            EventKind::Accepted,
+            EventKind::Completed,
+        ],
+    );
+
+    // Claimed, but the bot stopped before saving the channel it created.
+    let pending = Bounty::new(lister.clone(), hunter.clone(), 3, 25, String::from("USD"));
+    let pending_channel = api.add_text_channel("lister-hunter-bounty3", Some(bounties));
+    let pending_id = insert(pending, vec![EventKind::Confirming]);
+
+    // Pending, so any channel with its name belongs to an older bounty.
+    let stale = Bounty::new(lister.clone(), hunter.clone(), 4, 5, String::from("USD"));
+    let stale_id = insert(stale, Vec::new());
+    let orphan = api.add_text_channel("lister-hunter-bounty4", Some(archive));
+    let pending_orphan = api.add_text_channel("lister-hunter-bounty4", Some(bounties));
+    api.add_text_channel("general", None);
+
+    // Claimed, and the bot stopped after posting the intro but before saving the channel.
+    let introduced = Bounty::new(lister.clone(), hunter.clone(), 5, 5, String::from("USD"));
+    let introduced_channel = api.add_text_channel("lister-hunter-bounty5", Some(bounties));
+    api.send_message(introduced_channel, CreateMessage::default())
+        .await
+        .unwrap();
+    let introduced_id = insert(introduced, vec![EventKind::Confirming]);
+
+    // Claimed, and the bot stopped before the channel was created.
+    let unstarted = Bounty::new(lister.clone(), hunter.clone(), 6, 5, String::from("USD"));
+    let unstarted_id = insert(unstarted, vec![EventKind::Confirming]);
+
+    // Confirmed, but the bot lost access to its channel.
+    let locked = Bounty::new(lister.clone(), hunter.clone(), 7, 5, String::from("USD"));
+    let locked_channel = api.add_text_channel(&channel_name(&locked), Some(bounties));
+    insert(
+        locked,
+        vec![EventKind::Confirmed {
+            channel_id: locked_channel,
+        }],
+    );
+    api.set_overwrites(locked_channel, Vec::new())
+        .await
+        .unwrap();
+    api.deny_access(locked_channel);
+    api.take_calls();
+
+    let report = reconcile(&api, GUILD).await.unwrap();
+    assert_eq!(report.repaired, vec![(1, accepted_channel)]);
+    assert_eq!(report.missing, vec![(2, ChannelId(42))]);
+    assert_eq!(
+        report.relinked,
+        vec![(3, pending_channel), (5, introduced_channel)]
+    );
+    assert_eq!(report.reintroduced, vec![(3, pending_channel)]);
+    assert_eq!(report.released, vec![6]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, 7);
+    assert_eq!(report.failed[0].1, locked_channel);
+    assert_eq!(report.orphaned, vec![orphan, pending_orphan]);
+    assert!(report.missing_categories.is_empty());
+
+    let intros: Vec<_> = api
+        .take_calls()
+        .into_iter()
+        .filter(|call| call.method == "send_message")
+        .collect();
+    assert_eq!(intros.len(), 1);
+    assert_eq!(intros[0].target, pending_channel.to_string());
+    assert_eq!(
+        intros[0].body["components"][0]["components"][0]["label"],
+        "Accept"
+    );
+
+    let stale = store::get_bounty(&stale_id).unwrap();
+    assert_eq!(stale.status, BountyStatus::Pending);
+    assert_eq!(stale.channel_id, None);
+    assert_eq!(
+        store::get_bounty(&introduced_id).unwrap().status,
+        BountyStatus::Confirmed
+    );
+    assert_eq!(
+        store::get_bounty(&unstarted_id).unwrap().status,
+        BountyStatus::Pending
+    );
+
+    let overwrites = api.channel(accepted_channel).unwrap().overwrites;
+    assert!(overwrites.contains(&PermissionOverwrite {
+        allow: Permissions::VIEW_CHANNEL,
+        deny: Permissions::empty(),
+        kind: PermissionOverwriteType::Role(STAFF_ROLE),
+    }));
+    assert_eq!(overwrites, expected_permissions(GUILD, &accepted).unwrap());
+
+    let pending = store::get_bounty(&pending_id).unwrap();
+    assert_eq!(pending.status, BountyStatus::Confirmed);
+    assert_eq!(pending.channel_id, Some(pending_channel));
+    assert_eq!(
+        api.channel(pending_channel).unwrap().overwrites,
+        expected_permissions(GUILD, &pending).unwrap()
+    );
+    assert_eq!(
+        store::read(|store| store.ledger.escrowed(&pending_id)),
+        Some((25, String::from("USD")))
+    );
+
+    api.take_calls();
+    publish(&api, &report).await.unwrap();
+    let calls = api.take_calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].target, log.to_string());
+    let description = calls[0].body["embeds"][0]["description"].as_str().unwrap();
+    assert!(description.contains(&format!("<#{}> belongs to no bounty", orphan)));
+    assert!(description.contains(&format!("Could not fix <#{}>", locked_channel)));
+
+    let again = reconcile(&api, GUILD).await.unwrap();
+    assert!(again.relinked.is_empty() && again.repaired.is_empty());
+    assert!(again.reintroduced.is_empty() && again.released.is_empty());
+    assert_eq!(again.orphaned, vec![orphan, pending_orphan]);
+}
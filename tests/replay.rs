@@ -0,0 +1,16 @@
+//! Replays every scenario in `fixtures/` through the replay harness.
+
+use std::process::Command;
+
+#[test]
+fn recorded_scenarios_replay_cleanly() {
+    let output = Command::new(env!("CARGO_BIN_EXE_replay"))
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"))
+        .output()
+        .expect("Could not run the replay harness");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
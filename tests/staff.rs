@@ -0,0 +1,33 @@
+//! Checks the staff role config against itself and the guild.
+
+use std::env;
+
+use serenity::model::id::{GuildId, RoleId};
+
+use discord_bot_rust::commands::staff;
+use discord_bot_rust::discord_util::fake::FakeGuild;
+use discord_bot_rust::error::Error;
+
+#[tokio::test]
+async fn staff_roles_must_cover_every_action_and_exist() {
+    let api = FakeGuild::new(GuildId(700));
+    let staff_role = api.add_role("Staff");
+    let moderator = api.add_role("Moderators");
+
+    env::set_var("STAFF_ROLES", format!("{}, 799", staff_role));
+    assert!(staff::check().is_ok());
+    assert_eq!(
+        staff::missing_roles(&api, GuildId(700)).await.unwrap(),
+        vec![RoleId(799)]
+    );
+
+    env::set_var("COMPLETE_ROLES", moderator.to_string());
+    assert!(matches!(staff::check(), Err(Error::Config(_))));
+
+    env::set_var("STAFF_ROLES", format!("{},{}", staff_role, moderator));
+    assert!(staff::check().is_ok());
+    assert!(staff::missing_roles(&api, GuildId(700))
+        .await
+        .unwrap()
+        .is_empty());
+}
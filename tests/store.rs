@@ -0,0 +1,38 @@
+//! Saves the bounty store to disk.
+
+use std::{env, fs};
+
+use discord_bot_rust::storage::{ratings::Rating, store};
+use serenity::model::{id::UserId, Timestamp};
+use uuid::Uuid;
+
+fn rating() -> Rating {
+    Rating {
+        bounty_id: Uuid::new_v4(),
+        hunter: UserId(1),
+        rater: UserId(2),
+        score: 5,
+        comment: None,
+        timestamp: Timestamp::now(),
+    }
+}
+
+#[test]
+fn failed_saves_leave_memory_and_disk_untouched() {
+    let data_file = env::temp_dir().join(format!("store-{}.json", std::process::id()));
+    let _ = fs::remove_file(&data_file);
+    env::set_var("DATA_FILE", &data_file);
+
+    store::write(|store| store.ratings.push(rating())).unwrap();
+    let saved = fs::read_to_string(&data_file).unwrap();
+    assert!(!env::temp_dir()
+        .join(format!("store-{}.json.tmp", std::process::id()))
+        .exists());
+
+    env::set_var("DATA_FILE", "/nonexistent/bounties.json");
+    assert!(store::write(|store| store.ratings.push(rating())).is_err());
+    assert_eq!(store::read(|store| store.ratings.len()), 1);
+
+    env::set_var("DATA_FILE", &data_file);
+    assert_eq!(fs::read_to_string(&data_file).unwrap(), saved);
+}